@@ -4,7 +4,6 @@ use colored::Colorize;
 pub enum Level {
     Info,
     Success,
-    #[allow(dead_code)]
     Warn,
     Error,
 }
@@ -0,0 +1,63 @@
+use std::fs;
+
+use clap::Args;
+use serde_json::json;
+use vidascii_core::eval::{evaluate_image, find_best_options_for_image};
+
+use crate::{
+    console::{console_log, Level},
+    ConversionArgs,
+};
+
+/// Measure how close the braille art looks to the original image, printed as JSON
+///
+/// The braille art is decoded back to one pixel per dot, then both it and the resized original image are blurred
+/// before computing: PSNR (in dB, `null` when identical), SSIM (-1 to 1) and the mean brightness error
+/// (-1 to 1, negative when the art is too dark). Higher PSNR/SSIM is better.
+#[derive(Args, Debug)]
+pub struct EvalArgs {
+    /// Path to the image to convert and evaluate
+    #[arg(short, long)]
+    input: String,
+
+    #[command(flatten)]
+    conversion: ConversionArgs,
+}
+
+pub fn eval(args: &EvalArgs) {
+    let image_bytes = match fs::read(&args.input) {
+        Ok(image_bytes) => image_bytes,
+        Err(_) => {
+            console_log(format!("Failed to read '{}'", args.input), Level::Error);
+            return;
+        }
+    };
+
+    // with the auto preset, the chosen settings are the ones reported
+    let result = match args.conversion.is_auto() {
+        true => find_best_options_for_image(&image_bytes, args.conversion.ratio),
+        false => {
+            let options = args.conversion.img2braille_options();
+            evaluate_image(&image_bytes, Some(options)).map(|report| (options, report))
+        }
+    };
+    match result {
+        Ok((options, report)) => {
+            let report = json!({
+                "input": args.input,
+                "ratio": options.ratio,
+                "dithering": options.dithering,
+                "dithering_algorithm": format!("{:?}", options.dithering_algorithm),
+                "pattern_selection": format!("{:?}", options.pattern_selection),
+                "threshold": options.threshold,
+                "gamma": options.gamma,
+                "grayscale": options.grayscale.map_or("default".to_string(), |mode| format!("{mode:?}")),
+                "psnr": report.psnr,
+                "ssim": report.ssim,
+                "mean_brightness_error": report.mean_brightness_error,
+            });
+            println!("{report:#}");
+        }
+        Err(why) => console_log(format!("Failed to evaluate image: {why:?}"), Level::Error),
+    }
+}
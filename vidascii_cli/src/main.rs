@@ -1,35 +1,504 @@
 mod console;
+mod eval;
+mod rescale;
 
 use std::{
     fs::{self, OpenOptions},
     io::Write as FileWrite,
+    num::NonZeroUsize,
     path::Path,
     time::Duration,
 };
 
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use colored::Colorize;
 use console::{console_log, Level};
 use indicatif::{ProgressBar, ProgressStyle};
-use vidascii_core::{img2braille, vid2braille};
+use vidascii_core::{
+    braille2img::{
+        config::{
+            Braille2ImgOptions, Comparison, CrtEffect, DotShape, DotStyle, Gradient, ImageFormat,
+            OutputSize, PngCompression, Renderer, Theme,
+        },
+        font::CustomFont,
+        svg::SvgFont,
+    },
+    eval::find_best_options_for_image,
+    img2ascii::{self, config::Img2AsciiOptions},
+    img2braille::{
+        self,
+        config::{Img2BrailleOptions, Preset},
+    },
+    vid2braille::{
+        self,
+        config::{AudioMode, FrameRate, TimeRange, Vid2BrailleOptions},
+    },
+    CoreError,
+};
+
+use crate::{
+    eval::{eval, EvalArgs},
+    rescale::{rescale, RescaleArgs},
+};
 
 /// A simple *image/video* to braille *image/video* art converter 📼
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Path to the video or image file to convert
-    #[arg(short, long)]
-    input: String,
+    #[arg(short, long, required = true)]
+    input: Option<String>,
 
     /// Path to the output **directory** 📂
-    #[arg(short, long)]
-    output: String,
+    #[arg(short, long, required = true)]
+    output: Option<String>,
+
+    /// whether or not you want to use ascii characters instead of braille dots (images only)
+    ///
+    /// Each character is chosen by comparing the shape of every printable ascii glyph to the image,
+    /// so edges are sharper than with a brightness ramp. `--dithering` is ignored in this mode.
+    #[arg(short, long, default_value_t = false)]
+    ascii: bool,
+
+    #[command(flatten)]
+    conversion: ConversionArgs,
+
+    #[command(flatten)]
+    render: RenderArgs,
+
+    #[command(flatten)]
+    video: VideoArgs,
+}
+
+/// Settings of the output image/video rendering
+#[derive(clap::Args, Debug)]
+struct RenderArgs {
+    /// Format of the output image (videos are always mp4)
+    ///
+    /// - `raw`: RGBA pixels row by row, without any header
+    ///
+    /// - `svg`: scalable, braille characters as text rows (with `--renderer font`) or one shape per dot
+    ///   (with `--renderer dots`). Braille only
+    ///
+    /// - `html`: standalone page with the braille text in a `<pre>` block, see `--colored`. Braille only
+    #[arg(long, value_enum, default_value_t = OutputFormat::Png)]
+    format: OutputFormat,
+
+    /// With `--format html`, each character takes the color of the image area it covers
+    #[arg(long)]
+    colored: bool,
+
+    /// With `--format png`, `fast` is quicker to encode but bigger
+    #[arg(long, value_enum, default_value_t = PngCompressionArg::Best)]
+    png_compression: PngCompressionArg,
+
+    /// With `--format jpeg`, between 1 (smallest) and 100 (best looking)
+    #[arg(long, default_value_t = 90, value_parser = clap::value_parser!(u8).range(1..=100))]
+    jpeg_quality: u8,
+
+    /// With `--format svg`, load the font from this URL instead of embedding it in the svg
+    #[arg(long)]
+    svg_font_url: Option<String>,
+
+    /// Ready-made colors, `terminal-green` and `amber-crt` also enable `--glow` and `--scanlines`.
+    /// The other color and effect options override it
+    #[arg(long, value_enum)]
+    theme: Option<ThemeArg>,
+
+    /// Background color of the output, as `#rrggbb` or `r,g,b` [default: #000000]
+    #[arg(long, value_parser = parse_color)]
+    bg_color: Option<[u8; 3]>,
+
+    /// Draw only the characters on a transparent background, ignoring `--bg-color`.
+    /// Images only, and not with `--format jpeg`
+    #[arg(long)]
+    transparent: bool,
+
+    /// Color of the braille dots (or ascii characters), as `#rrggbb` or `r,g,b` [default: #ffffff]
+    #[arg(long, value_parser = parse_color)]
+    text_color: Option<[u8; 3]>,
+
+    /// Fades the text color to `--gradient-color` across the output
+    #[arg(long, value_enum, requires = "gradient_color")]
+    gradient: Option<GradientArg>,
+
+    /// End color of `--gradient`, as `#rrggbb` or `r,g,b`
+    #[arg(long, value_parser = parse_color, requires = "gradient")]
+    gradient_color: Option<[u8; 3]>,
+
+    /// Radius in pixels of a halo around the characters, 0 for none (not in svg)
+    #[arg(long, value_parser = parse_glow)]
+    glow: Option<f32>,
+
+    /// Dims every other row of pixels, like a CRT monitor (not in svg).
+    /// `--scanlines=false` turns off the ones of `--theme`
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    scanlines: Option<bool>,
+
+    /// Font size of the output in pixels, the characters size scales with it
+    #[arg(long, default_value_t = 20.0, value_parser = parse_font_size)]
+    font_size: f32,
+
+    /// Path to a TTF/OTF font replacing the embedded braille font (braille only),
+    /// characters missing from it are drawn with the embedded font
+    #[arg(long, value_parser = parse_font)]
+    font: Option<CustomFont>,
+
+    /// Pixel size of the output, `source` for the size of the input or `WIDTHxHEIGHT` (at most 100 megapixels).
+    /// The characters are scaled to fit [default: depends on --font-size] (not in html)
+    #[arg(long, value_parser = parse_size)]
+    size: Option<OutputSize>,
+
+    /// Shows the input next to the output (`side-by-side`) or under it (`overlay`, see `--overlay-opacity`),
+    /// at the size of the input. Braille only, not in svg and html
+    #[arg(long, value_enum)]
+    compare: Option<CompareArg>,
+
+    /// Opacity of the output over the input with `--compare overlay`, between 0 and 1
+    #[arg(long, default_value_t = 0.5, value_parser = parse_opacity)]
+    overlay_opacity: f32,
+
+    /// Empty border around the output in pixels
+    #[arg(long, default_value_t = 0)]
+    margin: u32,
+
+    /// Extra pixels between two lines of characters
+    #[arg(long, default_value_t = 0)]
+    line_spacing: u32,
+
+    /// Extra pixels between two characters
+    #[arg(long, default_value_t = 0)]
+    letter_spacing: u32,
+
+    /// How the braille characters are drawn (braille only)
+    ///
+    /// - `font`: with the bundled braille font, sized by `--font-size`
+    ///
+    /// - `dots`: each dot is drawn as a shape, pixel-exact at any size, sized by the `--dot-*` and `--cell-padding` options
+    #[arg(long, value_enum, default_value_t = RendererArg::Font)]
+    renderer: RendererArg,
+
+    /// Shape of the dots with `--renderer dots`
+    #[arg(long, value_enum, default_value_t = DotShapeArg::Circle)]
+    dot_shape: DotShapeArg,
+
+    /// Diameter of the dots in pixels with `--renderer dots`
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    dot_size: u32,
+
+    /// Gap between two dots of a same character in pixels with `--renderer dots`
+    #[arg(long, default_value_t = 1)]
+    dot_spacing: u32,
+
+    /// Empty border around each character in pixels with `--renderer dots`
+    #[arg(long, default_value_t = 1)]
+    cell_padding: u32,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Png,
+    Jpeg,
+    Webp,
+    Bmp,
+    Raw,
+    Svg,
+    Html,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum PngCompressionArg {
+    Fast,
+    Default,
+    Best,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum ThemeArg {
+    TerminalGreen,
+    AmberCrt,
+    Paper,
+    SolarizedLight,
+    SolarizedDark,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum GradientArg {
+    Vertical,
+    Horizontal,
+    Radial,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum CompareArg {
+    SideBySide,
+    Overlay,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum RendererArg {
+    Font,
+    Dots,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum DotShapeArg {
+    Circle,
+    Square,
+}
+
+impl RenderArgs {
+    /// `None` for svg and html
+    fn image_format(&self) -> Option<ImageFormat> {
+        let format = match self.format {
+            OutputFormat::Png => ImageFormat::Png(match self.png_compression {
+                PngCompressionArg::Fast => PngCompression::Fast,
+                PngCompressionArg::Default => PngCompression::Default,
+                PngCompressionArg::Best => PngCompression::Best,
+            }),
+            OutputFormat::Jpeg => ImageFormat::Jpeg(self.jpeg_quality),
+            OutputFormat::Webp => ImageFormat::WebP,
+            OutputFormat::Bmp => ImageFormat::Bmp,
+            OutputFormat::Raw => ImageFormat::RawRgba,
+            OutputFormat::Svg | OutputFormat::Html => return None,
+        };
+        Some(format)
+    }
+
+    fn braille2img_options(&self) -> Braille2ImgOptions {
+        let renderer = match self.renderer {
+            RendererArg::Font => Renderer::Font,
+            RendererArg::Dots => Renderer::Dots(DotStyle {
+                shape: match self.dot_shape {
+                    DotShapeArg::Circle => DotShape::Circle,
+                    DotShapeArg::Square => DotShape::Square,
+                },
+                diameter: self.dot_size,
+                spacing: self.dot_spacing,
+                cell_padding: self.cell_padding,
+            }),
+        };
+
+        let theme = self.theme.map(|theme| match theme {
+            ThemeArg::TerminalGreen => Theme::TerminalGreen,
+            ThemeArg::AmberCrt => Theme::AmberCrt,
+            ThemeArg::Paper => Theme::Paper,
+            ThemeArg::SolarizedLight => Theme::SolarizedLight,
+            ThemeArg::SolarizedDark => Theme::SolarizedDark,
+        });
+        let mut builder = Braille2ImgOptions::builder();
+        if let Some(theme) = theme {
+            builder = builder.set_theme(theme);
+        }
+        if let Some(bg_color) = self.bg_color {
+            builder = builder.set_bg_color(bg_color);
+        }
+        if let Some(text_color) = self.text_color {
+            builder = builder.set_text_color(text_color);
+        }
+        if let (Some(gradient), Some(to)) = (self.gradient, self.gradient_color) {
+            builder = builder.set_gradient(match gradient {
+                GradientArg::Vertical => Gradient::Vertical(to),
+                GradientArg::Horizontal => Gradient::Horizontal(to),
+                GradientArg::Radial => Gradient::Radial(to),
+            });
+        }
+        if let Some(compare) = self.compare {
+            builder = builder.set_comparison(match compare {
+                CompareArg::SideBySide => Comparison::SideBySide,
+                CompareArg::Overlay => Comparison::Overlay(self.overlay_opacity),
+            });
+        }
+        // on top of the theme effect
+        let theme_effect = theme.map(Theme::effect).unwrap_or_default();
+        builder = builder.set_effect(CrtEffect {
+            glow: self.glow.unwrap_or(theme_effect.glow),
+            scanlines: self.scanlines.unwrap_or(theme_effect.scanlines),
+        });
+
+        let builder = builder
+            .set_size(self.size.unwrap_or_default())
+            .set_transparent_background(self.transparent)
+            .set_font_size(self.font_size)
+            .set_margin(self.margin)
+            .set_line_spacing(self.line_spacing)
+            .set_letter_spacing(self.letter_spacing)
+            .set_renderer(renderer)
+            .set_format(self.image_format().unwrap_or_default());
+        match self.font {
+            Some(ref font) => builder.set_font(font.clone()).build(),
+            None => builder.build(),
+        }
+    }
+}
+
+/// Settings of the video conversion
+#[derive(clap::Args, Debug)]
+struct VideoArgs {
+    /// Frames per second of the output video, `source` to keep the rate of the input
+    #[arg(long, default_value = "12", value_parser = parse_fps)]
+    fps: FrameRate,
+
+    /// Audio of the output video
+    ///
+    /// - `none`: silent
+    ///
+    /// - `copy`: the audio of the input as is, fails when its codec does not fit in mp4
+    ///
+    /// - `aac`: the audio of the input, re-encoded
+    #[arg(long, value_enum, default_value_t = AudioArg::None)]
+    audio: AudioArg,
+
+    /// Seconds of the input skipped before converting (the audio is cut the same way)
+    #[arg(long, default_value_t = 0.0, value_parser = parse_seconds)]
+    start: f64,
+
+    /// Seconds of the input to convert from `--start` [default: until the end]
+    #[arg(long, value_parser = parse_duration)]
+    duration: Option<f64>,
+
+    /// Frames converted at the same time [default: the number of CPUs]
+    #[arg(long)]
+    jobs: Option<NonZeroUsize>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum AudioArg {
+    None,
+    Copy,
+    Aac,
+}
+
+impl VideoArgs {
+    fn vid2braille_options(&self) -> Vid2BrailleOptions {
+        let builder = Vid2BrailleOptions::builder()
+            .set_frame_rate(self.fps)
+            .set_audio(match self.audio {
+                AudioArg::None => AudioMode::Drop,
+                AudioArg::Copy => AudioMode::Copy,
+                AudioArg::Aac => AudioMode::Aac,
+            })
+            .set_range(TimeRange {
+                start: self.start,
+                duration: self.duration,
+            });
+        match self.jobs {
+            Some(jobs) => builder.set_jobs(jobs).build(),
+            None => builder.build(),
+        }
+    }
+}
+
+fn parse_color(color: &str) -> Result<[u8; 3], String> {
+    let invalid = || format!("'{color}' is not a `#rrggbb` or `r,g,b` color");
 
+    if let Some(hex) = color.strip_prefix('#') {
+        if hex.len() != 6 || !hex.is_ascii() {
+            return Err(invalid());
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+        return Ok([channel(0)?, channel(2)?, channel(4)?]);
+    }
+
+    let channels = color
+        .split(',')
+        .map(|channel| channel.trim().parse::<u8>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    channels.try_into().map_err(|_| invalid())
+}
+
+fn parse_font(path: &str) -> Result<CustomFont, String> {
+    CustomFont::from_path(Path::new(path)).map_err(|why| match why {
+        CoreError::FileNotFound => format!("'{path}' does not seem to exist..."),
+        _ => format!("'{path}' is not a valid TTF/OTF font"),
+    })
+}
+
+/// pixels of a `--size`, an rgba image of this size already takes 400MB
+const MAX_PIXELS: u64 = 100_000_000;
+
+fn parse_size(size: &str) -> Result<OutputSize, String> {
+    if size == "source" {
+        return Ok(OutputSize::Source);
+    }
+    let parsed = size.split_once('x').and_then(|(width, height)| {
+        Some((width.parse::<u32>().ok()?, height.parse::<u32>().ok()?))
+    });
+    match parsed {
+        Some((width, height)) if width as u64 * height as u64 > MAX_PIXELS => Err(format!(
+            "'{size}' is too big, expected at most {MAX_PIXELS} pixels"
+        )),
+        Some((width, height)) if width > 0 && height > 0 => Ok(OutputSize::Pixels(width, height)),
+        _ => Err(format!("'{size}' is not `source` or a `WIDTHxHEIGHT` size")),
+    }
+}
+
+fn parse_opacity(opacity: &str) -> Result<f32, String> {
+    match opacity.parse::<f32>() {
+        Ok(opacity) if (0.0..=1.0).contains(&opacity) => Ok(opacity),
+        _ => Err(format!(
+            "'{opacity}' is not an opacity, expected a number between 0 and 1"
+        )),
+    }
+}
+
+fn parse_fps(fps: &str) -> Result<FrameRate, String> {
+    if fps == "source" {
+        return Ok(FrameRate::Source);
+    }
+    match fps.parse::<f32>() {
+        Ok(fps) if fps.is_finite() && fps > 0.0 => Ok(FrameRate::Fixed(fps)),
+        _ => Err(format!(
+            "'{fps}' is not `source` or a frame rate, expected a number > 0"
+        )),
+    }
+}
+
+fn parse_seconds(seconds: &str) -> Result<f64, String> {
+    match seconds.parse::<f64>() {
+        Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => Ok(seconds),
+        _ => Err(format!(
+            "'{seconds}' is not a time, expected a number of seconds >= 0"
+        )),
+    }
+}
+
+fn parse_duration(duration: &str) -> Result<f64, String> {
+    match parse_seconds(duration)? {
+        0.0 => Err("the duration must be more than 0 seconds".to_string()),
+        duration => Ok(duration),
+    }
+}
+
+fn parse_glow(radius: &str) -> Result<f32, String> {
+    match radius.parse::<f32>() {
+        Ok(radius) if radius.is_finite() && radius >= 0.0 => Ok(radius),
+        _ => Err(format!(
+            "'{radius}' is not a glow radius, expected a number >= 0"
+        )),
+    }
+}
+
+fn parse_font_size(size: &str) -> Result<f32, String> {
+    match size.parse::<f32>() {
+        Ok(size) if size.is_finite() && size >= 1.0 => Ok(size),
+        _ => Err(format!(
+            "'{size}' is not a font size, expected a number >= 1"
+        )),
+    }
+}
+
+/// Settings of the image to braille conversion
+#[derive(clap::Args, Debug)]
+struct ConversionArgs {
     /// The 'ratio' is the amount of braille characters dots per pixel.
     /// For exemple:
     ///
     /// - `-ratio=1.0` mean that, one braille dot = one pixel, in other words each pixel is conserved on the ouput,
-    /// original image quality is conserved
+    ///   original image quality is conserved
     ///
     /// - `-ratio=2.0` mean that, one braille dot = 4 pixels, so there is a data lost, output image quality will be degraded
     ///
@@ -48,6 +517,112 @@ struct Args {
     /// @see: https://en.wikipedia.org/wiki/Floyd%E2%80%93Steinberg_dithering
     #[arg(short, long, default_value_t = true)]
     dithering: bool,
+
+    /// ready-made settings, overrides `--dithering`
+    ///
+    /// - `fast`: each dot is raised when its pixels are bright enough, with dithering
+    ///
+    /// - `quality`: each character is the braille pattern (or ascii glyph) closest to the image,
+    ///   the remaining error is spread to the neighbor characters. Slower but sharper
+    ///
+    /// - `auto`: tries combinations of threshold, dithering algorithm, gamma and grayscale mode
+    ///   and keeps the one looking the closest to the image (SSIM). Braille images only,
+    ///   falls back to `quality` otherwise
+    #[arg(short, long, value_enum)]
+    preset: Option<PresetArg>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    Rescale(RescaleArgs),
+    Eval(EvalArgs),
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum PresetArg {
+    Fast,
+    Quality,
+    Auto,
+}
+
+impl PresetArg {
+    /// `auto` has no fixed settings, `quality` is used where the search is not available
+    fn fixed_preset(self) -> Preset {
+        match self {
+            PresetArg::Fast => Preset::Fast,
+            PresetArg::Quality | PresetArg::Auto => Preset::Quality,
+        }
+    }
+}
+
+impl ConversionArgs {
+    fn is_auto(&self) -> bool {
+        matches!(self.preset, Some(PresetArg::Auto))
+    }
+
+    fn img2braille_options(&self) -> Img2BrailleOptions {
+        let builder = Img2BrailleOptions::builder()
+            .set_ratio(self.ratio)
+            .set_dithering(self.dithering);
+        match self.preset {
+            Some(preset) => builder.set_preset(preset.fixed_preset()).build(),
+            None => builder.build(),
+        }
+    }
+
+    fn img2ascii_options(&self) -> Img2AsciiOptions {
+        let builder = Img2AsciiOptions::builder();
+        match self.preset {
+            Some(preset) => builder.set_preset(preset.fixed_preset()).build(),
+            None => builder.build(),
+        }
+    }
+
+    /// runs the `auto` search on the image when requested, the fixed options otherwise
+    fn img2braille_options_for(&self, image_bytes: &[u8]) -> Img2BrailleOptions {
+        if !self.is_auto() {
+            return self.img2braille_options();
+        }
+
+        console_log("Searching the best settings... 🔎", Level::Info);
+        match find_best_options_for_image(image_bytes, self.ratio) {
+            Ok((options, report)) => {
+                console_log(
+                    format!(
+                        "Best settings: {} (SSIM {:.3})",
+                        describe_options(&options),
+                        report.ssim
+                    ),
+                    Level::Info,
+                );
+                options
+            }
+            Err(why) => {
+                console_log(
+                    format!("Failed to search settings ({why:?}), using `quality`"),
+                    Level::Warn,
+                );
+                self.img2braille_options()
+            }
+        }
+    }
+}
+
+fn describe_options(options: &Img2BrailleOptions) -> String {
+    let dithering = match options.dithering {
+        true => format!("{:?}", options.dithering_algorithm),
+        false => "none".to_string(),
+    };
+    format!(
+        "pattern selection: {:?}, threshold: {}, dithering: {}, gamma: {}, grayscale: {}",
+        options.pattern_selection,
+        options.threshold,
+        dithering,
+        options.gamma,
+        options
+            .grayscale
+            .map_or("default".to_string(), |mode| format!("{mode:?}"))
+    )
 }
 
 fn check_io(input: &Path, output: &Path) -> Result<(), ()> {
@@ -101,6 +676,15 @@ fn check_io(input: &Path, output: &Path) -> Result<(), ()> {
     }
 }
 
+fn save_output(output_file: &str, datas: &[u8]) -> std::io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(output_file)?;
+    file.write_all(datas)
+}
+
 fn init_progress_bar() -> (ProgressBar, impl Fn(&'static str, u8)) {
     let pb = ProgressBar::new(100);
 
@@ -124,9 +708,22 @@ fn init_progress_bar() -> (ProgressBar, impl Fn(&'static str, u8)) {
 
 fn main() {
     let args = Args::parse();
+    // keep stdout parsable
+    if let Some(Command::Eval(ref eval_args)) = args.command {
+        return eval(eval_args);
+    }
     console_log("Welcome to vidascii!".bold(), Level::Info);
 
-    let (input, output) = (Path::new(&args.input), Path::new(&args.output));
+    match args.command {
+        Some(Command::Rescale(ref rescale_args)) => return rescale(rescale_args),
+        Some(Command::Eval(_)) | None => {}
+    }
+
+    // required when there is no subcommand
+    let (Some(input), Some(output)) = (&args.input, &args.output) else {
+        return;
+    };
+    let (input, output) = (Path::new(input), Path::new(output));
     if check_io(input, output).is_err() {
         return;
     }
@@ -149,13 +746,35 @@ fn main() {
     match mime_type {
         mime_guess::mime::VIDEO => {
             console_log("📼 Video detected. Converting... ⏳", Level::Info);
+            if args.ascii {
+                console_log(
+                    "ascii mode is not supported for videos, using braille",
+                    Level::Warn,
+                );
+            }
+            if args.conversion.is_auto() {
+                console_log(
+                    "auto preset is not supported for videos, using quality",
+                    Level::Warn,
+                );
+            }
+            if args.render.format != OutputFormat::Png {
+                console_log("videos are always encoded to mp4", Level::Warn);
+            }
+            if args.render.transparent {
+                console_log(
+                    "mp4 has no transparency, using the background color",
+                    Level::Warn,
+                );
+            }
 
             let (progress_bar, set_progress) = init_progress_bar();
             let convertion_result = vid2braille::video_to_braille(
                 input,
                 output,
-                args.ratio,
-                args.dithering,
+                Some(args.conversion.img2braille_options()),
+                Some(args.render.braille2img_options()),
+                Some(args.video.vid2braille_options()),
                 set_progress,
             );
             progress_bar.finish();
@@ -177,35 +796,106 @@ fn main() {
             console_log("🖼 Image detected.", Level::Info);
 
             let image_bytes = fs::read(input).unwrap();
+            if args.video.audio != AudioArg::None
+                || args.video.start > 0.0
+                || args.video.duration.is_some()
+                || args.video.jobs.is_some()
+            {
+                console_log(
+                    "--audio, --start, --duration and --jobs are only supported for videos",
+                    Level::Warn,
+                );
+            }
+            if args.ascii && args.conversion.is_auto() {
+                console_log(
+                    "auto preset is not supported in ascii mode, using quality",
+                    Level::Warn,
+                );
+            }
+            let format = match (args.ascii, args.render.format) {
+                (true, format @ (OutputFormat::Svg | OutputFormat::Html)) => {
+                    console_log(
+                        format!(
+                            "{} is not supported in ascii mode, using png",
+                            format!("{format:?}").to_lowercase()
+                        ),
+                        Level::Warn,
+                    );
+                    OutputFormat::Png
+                }
+                (_, format) => format,
+            };
+            if args.render.compare.is_some()
+                && (args.ascii || matches!(format, OutputFormat::Svg | OutputFormat::Html))
+            {
+                console_log(
+                    "--compare is only supported for braille raster images, ignoring it",
+                    Level::Warn,
+                );
+            }
+            if args.render.colored && format != OutputFormat::Html {
+                console_log("--colored is only supported in html", Level::Warn);
+            }
+            if args.render.transparent && args.render.format == OutputFormat::Jpeg {
+                console_log(
+                    "jpeg has no transparency, using the background color",
+                    Level::Warn,
+                );
+            }
+            let braille_options = match args.ascii {
+                true => args.conversion.img2braille_options(),
+                false => args.conversion.img2braille_options_for(&image_bytes),
+            };
             console_log("Image loaded. Converting... ⏳", Level::Info);
 
             let (progress_bar, set_progress) = init_progress_bar();
-            let conversion_result = img2braille::image_to_braille(
-                &image_bytes,
-                args.ratio,
-                args.dithering,
-                Some(set_progress),
-            );
+            let conversion_result = match (args.ascii, format) {
+                (true, _) => img2ascii::image_to_ascii(
+                    &image_bytes,
+                    args.conversion.ratio,
+                    Some(args.conversion.img2ascii_options()),
+                    Some(args.render.braille2img_options()),
+                    Some(set_progress),
+                ),
+                (false, OutputFormat::Html) => img2braille::image_to_braille_html(
+                    &image_bytes,
+                    Some(braille_options),
+                    Some(args.render.braille2img_options()),
+                    args.render.colored,
+                    Some(set_progress),
+                )
+                .map(String::into_bytes),
+                (false, OutputFormat::Svg) => img2braille::image_to_braille_svg(
+                    &image_bytes,
+                    Some(braille_options),
+                    Some(args.render.braille2img_options()),
+                    match args.render.svg_font_url {
+                        Some(ref url) => SvgFont::Link(url.clone()),
+                        None => SvgFont::Embed,
+                    },
+                    Some(set_progress),
+                )
+                .map(String::into_bytes),
+                (false, _) => img2braille::image_to_braille(
+                    &image_bytes,
+                    Some(braille_options),
+                    Some(args.render.braille2img_options()),
+                    Some(set_progress),
+                ),
+            };
             progress_bar.finish();
 
             match conversion_result {
                 Ok(out_img_datas) => {
                     console_log("Image successfully converted! Saving... 💾", Level::Success);
 
-                    let output_file = format!("{}/output.png", output.display());
-                    let mut file = match OpenOptions::new()
-                        .create(true)
-                        .write(true)
-                        .truncate(true)
-                        .open(&output_file)
-                    {
-                        Ok(f) => f,
-                        Err(_) => {
-                            console_log("Failed to save image", Level::Error);
-                            return;
-                        }
+                    let extension = match format {
+                        OutputFormat::Svg => "svg",
+                        OutputFormat::Html => "html",
+                        _ => args.render.image_format().unwrap_or_default().extension(),
                     };
-                    if file.write_all(&out_img_datas).is_err() {
+                    let output_file = format!("{}/output.{}", output.display(), extension);
+                    if save_output(&output_file, &out_img_datas).is_err() {
                         console_log("Failed to save image", Level::Error);
                         return;
                     }
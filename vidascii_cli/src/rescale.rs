@@ -0,0 +1,108 @@
+use std::{fs, path::Path};
+
+use clap::{Args, ValueEnum};
+use colored::Colorize;
+use vidascii_core::braille::{decode, ResampleFilter};
+
+use crate::{
+    check_io,
+    console::{console_log, Level},
+    save_output,
+};
+
+/// Rescale existing braille art (a `.txt` file) without the original image
+#[derive(Args, Debug)]
+pub struct RescaleArgs {
+    /// Path to the braille art text file, anything that is not braille is ignored
+    #[arg(short, long)]
+    input: String,
+
+    /// Path to the output **directory** 📂
+    #[arg(short, long)]
+    output: String,
+
+    /// e.g. `2` doubles the art size, `0.5` halves it
+    #[arg(short, long, value_parser = parse_scale)]
+    scale: f32,
+
+    /// how new dots are created when enlarging (shrinking always keeps the dots covering at least half of their area)
+    #[arg(short, long, value_enum, default_value_t = FilterArg::Epx)]
+    filter: FilterArg,
+}
+
+fn parse_scale(scale: &str) -> Result<f32, String> {
+    match scale.parse::<f32>() {
+        Ok(scale) if scale.is_finite() && scale > 0.0 => Ok(scale),
+        _ => Err(format!(
+            "'{scale}' is not a scale, expected a number greater than 0"
+        )),
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum FilterArg {
+    /// blocky
+    Nearest,
+    /// smooth diagonals
+    Epx,
+}
+
+impl From<FilterArg> for ResampleFilter {
+    fn from(filter: FilterArg) -> Self {
+        match filter {
+            FilterArg::Nearest => ResampleFilter::Nearest,
+            FilterArg::Epx => ResampleFilter::Epx,
+        }
+    }
+}
+
+pub fn rescale(args: &RescaleArgs) {
+    let (input, output) = (Path::new(&args.input), Path::new(&args.output));
+    if check_io(input, output).is_err() {
+        return;
+    }
+
+    let text = match fs::read_to_string(input) {
+        Ok(text) => text,
+        Err(_) => {
+            console_log("Failed to read input file as text", Level::Error);
+            return;
+        }
+    };
+
+    let art = decode(&text);
+    if art.width() == 0 {
+        console_log("No braille found in the input file", Level::Error);
+        return;
+    }
+    let Some(rescaled) = art.scale(args.scale, args.filter.into()) else {
+        console_log(
+            "The rescaled art would be too big, try a smaller scale",
+            Level::Error,
+        );
+        return;
+    };
+    console_log(
+        format!(
+            "Rescaled from {}x{} to {}x{} characters. Saving... 💾",
+            art.width(),
+            art.height(),
+            rescaled.width(),
+            rescaled.height()
+        ),
+        Level::Success,
+    );
+
+    let output_file = format!("{}/output.txt", output.display());
+    if save_output(&output_file, rescaled.to_string().as_bytes()).is_err() {
+        console_log("Failed to save braille art", Level::Error);
+        return;
+    }
+    console_log(
+        format!(
+            "Braille art saved sucessfully! Check: {}",
+            output_file.bold().italic()
+        ),
+        Level::Success,
+    );
+}
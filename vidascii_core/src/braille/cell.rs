@@ -0,0 +1,133 @@
+use std::{
+    fmt,
+    ops::{BitAnd, BitOr, Not},
+};
+
+/// dots per cell, horizontally
+pub const CELL_WIDTH: usize = 2;
+/// dots per cell, vertically
+pub const CELL_HEIGHT: usize = 4;
+
+const BRAILLE_BASE: u32 = 0x2800;
+
+/// standard dot number (1 to 8) of each `[x][y]` position, the bit of a dot in the unicode offset is `dot - 1`
+const DOT_NUMBERS: [[u8; CELL_HEIGHT]; CELL_WIDTH] = [[1, 2, 3, 7], [4, 5, 6, 8]];
+
+/// A single braille character.
+///
+/// Internally this is the offset of the character from `U+2800`: the bit `n` is raised when the dot `n + 1` is.
+/// Positions are `(x, y)` with `x` in `0..2` (left to right) and `y` in `0..4` (top to bottom)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BrailleCell(pub u8);
+
+impl BrailleCell {
+    pub const EMPTY: Self = Self(0);
+    pub const FULL: Self = Self(u8::MAX);
+
+    /// `dots` use the standard 1 to 8 numbering, returns `None` if one of them is out of range
+    pub fn from_dots(dots: &[u8]) -> Option<Self> {
+        dots.iter().try_fold(Self::EMPTY, |cell, &dot| {
+            (1..=8)
+                .contains(&dot)
+                .then(|| Self(cell.0 | 1 << (dot - 1)))
+        })
+    }
+
+    /// raised dots, in the standard 1 to 8 numbering
+    pub fn dots(self) -> Vec<u8> {
+        (1..=8).filter(|dot| self.0 & 1 << (dot - 1) != 0).collect()
+    }
+
+    /// standard number (1 to 8) of the dot at `(x, y)`, `None` out of the cell
+    pub fn dot_number(x: usize, y: usize) -> Option<u8> {
+        DOT_NUMBERS.get(x)?.get(y).copied()
+    }
+
+    /// `(x, y)` position of a standard dot number (1 to 8)
+    pub fn dot_position(dot: u8) -> Option<(usize, usize)> {
+        (0..CELL_WIDTH)
+            .flat_map(|x| (0..CELL_HEIGHT).map(move |y| (x, y)))
+            .find(|&(x, y)| DOT_NUMBERS[x][y] == dot)
+    }
+
+    /// 0 out of the cell
+    fn mask(x: usize, y: usize) -> u8 {
+        Self::dot_number(x, y).map_or(0, |dot| 1 << (dot - 1))
+    }
+
+    /// `false` out of the cell
+    pub fn get(self, x: usize, y: usize) -> bool {
+        self.0 & Self::mask(x, y) != 0
+    }
+
+    /// raise the dot at `(x, y)`, does nothing out of the cell
+    pub fn set(&mut self, x: usize, y: usize) {
+        self.0 |= Self::mask(x, y);
+    }
+
+    /// lower the dot at `(x, y)`, does nothing out of the cell
+    pub fn clear(&mut self, x: usize, y: usize) {
+        self.0 &= !Self::mask(x, y);
+    }
+
+    /// number of raised dots
+    pub fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn invert(self) -> Self {
+        Self(!self.0)
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub fn intersect(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    pub fn to_char(self) -> char {
+        // every offset between 0 and 255 is a valid braille character
+        char::from_u32(BRAILLE_BASE + self.0 as u32).unwrap_or(' ')
+    }
+
+    /// `None` if `c` is not a braille pattern (U+2800 to U+28FF)
+    pub fn from_char(c: char) -> Option<Self> {
+        let offset = (c as u32).checked_sub(BRAILLE_BASE)?;
+        u8::try_from(offset).ok().map(Self)
+    }
+}
+
+impl From<BrailleCell> for char {
+    fn from(cell: BrailleCell) -> Self {
+        cell.to_char()
+    }
+}
+
+impl fmt::Display for BrailleCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl Not for BrailleCell {
+    type Output = Self;
+    fn not(self) -> Self::Output {
+        self.invert()
+    }
+}
+
+impl BitOr for BrailleCell {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.union(rhs)
+    }
+}
+
+impl BitAnd for BrailleCell {
+    type Output = Self;
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.intersect(rhs)
+    }
+}
@@ -0,0 +1,58 @@
+use image::GrayImage;
+
+use super::{BrailleCell, BrailleImage};
+
+/// Lenient parser for braille art copied from chats, files, etc.
+///
+/// - any character that is not a braille pattern (spaces, letters, emojis...) is an empty cell
+/// - zero-width and control characters (`\r`, BOM, variation selectors...) are ignored
+/// - each line ends at its last braille character, lines without any are empty,
+///   and leading/trailing lines without braille are dropped
+/// - ragged lines are padded with empty cells
+///
+/// Use `str::parse::<BrailleImage>` instead to reject anything that is not braille
+pub fn decode(text: &str) -> BrailleImage {
+    let mut lines = text
+        .lines()
+        .map(|line| {
+            let mut cells = line
+                .chars()
+                .filter(|&c| !is_invisible(c))
+                .map(BrailleCell::from_char)
+                .collect::<Vec<_>>();
+
+            // drop what comes after the art
+            let art_len = cells
+                .iter()
+                .rposition(|cell| cell.is_some())
+                .map_or(0, |last| last + 1);
+            cells.truncate(art_len);
+
+            cells
+                .into_iter()
+                .map(|cell| cell.unwrap_or(BrailleCell::EMPTY))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let first = lines.iter().position(|line| !line.is_empty());
+    let last = lines.iter().rposition(|line| !line.is_empty());
+    match (first, last) {
+        (Some(first), Some(last)) => {
+            lines.truncate(last + 1);
+            lines.drain(..first);
+            BrailleImage::from_cells(lines)
+        }
+        _ => BrailleImage::default(),
+    }
+}
+
+/// `decode` then `BrailleImage::to_bitmap`: one pixel per dot, 255 when raised
+pub fn decode_to_bitmap(text: &str) -> GrayImage {
+    decode(text).to_bitmap()
+}
+
+fn is_invisible(c: char) -> bool {
+    (c.is_control() && c != '\t')
+        || matches!(c, '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FE00}'..='\u{FE0F}' | '\u{FEFF}')
+}
@@ -0,0 +1,287 @@
+use std::{fmt, str::FromStr};
+
+use image::{GrayImage, Luma};
+
+use crate::CoreError;
+
+use super::{BrailleCell, CELL_HEIGHT, CELL_WIDTH};
+
+/// How two overlapping cells are combined, dot by dot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoolOp {
+    /// raised in either
+    Or,
+    /// raised in both
+    And,
+    /// raised in only one of them
+    Xor,
+    /// raised in the bottom image but not in the top one
+    Subtract,
+    /// the top image replaces the bottom one
+    Replace,
+}
+
+impl BoolOp {
+    pub fn apply(self, bottom: BrailleCell, top: BrailleCell) -> BrailleCell {
+        BrailleCell(match self {
+            BoolOp::Or => bottom.0 | top.0,
+            BoolOp::And => bottom.0 & top.0,
+            BoolOp::Xor => bottom.0 ^ top.0,
+            BoolOp::Subtract => bottom.0 & !top.0,
+            BoolOp::Replace => top.0,
+        })
+    }
+}
+
+/// A grid of braille characters.
+///
+/// `width` and `height` are in cells, each cell being `CELL_WIDTH`x`CELL_HEIGHT` dots.
+/// Dots coordinates (`*_dot` methods) are relative to the whole image
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BrailleImage {
+    width: usize,
+    height: usize,
+    /// row-major
+    cells: Vec<BrailleCell>,
+}
+
+impl BrailleImage {
+    /// empty image
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![BrailleCell::EMPTY; width * height],
+        }
+    }
+
+    /// shorter lines are padded with empty cells
+    pub fn from_cells(lines: Vec<Vec<BrailleCell>>) -> Self {
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+        let height = lines.len();
+
+        let mut cells = Vec::with_capacity(width * height);
+        for mut line in lines {
+            line.resize(width, BrailleCell::EMPTY);
+            cells.extend(line);
+        }
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// builds an image of `dots_width`x`dots_height` dots, rounded up to whole cells
+    pub fn from_dot_fn(
+        dots_width: usize,
+        dots_height: usize,
+        is_raised: impl Fn(usize, usize) -> bool,
+    ) -> Self {
+        let mut image = Self::new(
+            dots_width.div_ceil(CELL_WIDTH),
+            dots_height.div_ceil(CELL_HEIGHT),
+        );
+        for y in 0..dots_height {
+            for x in 0..dots_width {
+                if is_raised(x, y) {
+                    image.set_dot(x, y, true);
+                }
+            }
+        }
+        image
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn dots_width(&self) -> usize {
+        self.width * CELL_WIDTH
+    }
+
+    pub fn dots_height(&self) -> usize {
+        self.height * CELL_HEIGHT
+    }
+
+    pub fn cells(&self) -> &[BrailleCell] {
+        &self.cells
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &[BrailleCell]> {
+        // `chunks` panics on 0
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<BrailleCell> {
+        (x < self.width && y < self.height).then(|| self.cells[y * self.width + x])
+    }
+
+    /// does nothing out of bounds
+    pub fn set(&mut self, x: usize, y: usize, cell: BrailleCell) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = cell;
+        }
+    }
+
+    /// `false` out of bounds
+    pub fn get_dot(&self, x: usize, y: usize) -> bool {
+        self.get(x / CELL_WIDTH, y / CELL_HEIGHT)
+            .is_some_and(|cell| cell.get(x % CELL_WIDTH, y % CELL_HEIGHT))
+    }
+
+    /// does nothing out of bounds
+    pub fn set_dot(&mut self, x: usize, y: usize, raised: bool) {
+        let Some(mut cell) = self.get(x / CELL_WIDTH, y / CELL_HEIGHT) else {
+            return;
+        };
+        match raised {
+            true => cell.set(x % CELL_WIDTH, y % CELL_HEIGHT),
+            false => cell.clear(x % CELL_WIDTH, y % CELL_HEIGHT),
+        }
+        self.set(x / CELL_WIDTH, y / CELL_HEIGHT, cell);
+    }
+
+    /// the part of the image inside the given rectangle (in cells), clipped to the image bounds
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> Self {
+        let (x, y) = (x.min(self.width), y.min(self.height));
+        let (width, height) = (width.min(self.width - x), height.min(self.height - y));
+
+        let mut cropped = Self::new(width, height);
+        for cy in 0..height {
+            for cx in 0..width {
+                cropped.cells[cy * width + cx] = self.cells[(y + cy) * self.width + x + cx];
+            }
+        }
+        cropped
+    }
+
+    /// adds empty cells around the image
+    pub fn pad(&self, top: usize, right: usize, bottom: usize, left: usize) -> Self {
+        let mut padded = Self::new(left + self.width + right, top + self.height + bottom);
+        padded.paste(self, left, top, BoolOp::Replace);
+        padded
+    }
+
+    /// `other` is put at the right of `self`, the shortest one is padded at the bottom
+    pub fn concat_horizontal(&self, other: &Self) -> Self {
+        let mut concatenated = Self::new(self.width + other.width, self.height.max(other.height));
+        concatenated.paste(self, 0, 0, BoolOp::Replace);
+        concatenated.paste(other, self.width, 0, BoolOp::Replace);
+        concatenated
+    }
+
+    /// `other` is put below `self`, the thinnest one is padded at the right
+    pub fn concat_vertical(&self, other: &Self) -> Self {
+        let mut concatenated = Self::new(self.width.max(other.width), self.height + other.height);
+        concatenated.paste(self, 0, 0, BoolOp::Replace);
+        concatenated.paste(other, 0, self.height, BoolOp::Replace);
+        concatenated
+    }
+
+    /// combines `top` with this image, `top` being placed at the `(x, y)` cell. The result keeps this image size
+    pub fn overlay(&self, top: &Self, x: usize, y: usize, op: BoolOp) -> Self {
+        let mut overlaid = self.clone();
+        overlaid.paste(top, x, y, op);
+        overlaid
+    }
+
+    fn paste(&mut self, top: &Self, x: usize, y: usize, op: BoolOp) {
+        for (ty, line) in top.lines().enumerate() {
+            for (tx, &cell) in line.iter().enumerate() {
+                if let Some(bottom) = self.get(x + tx, y + ty) {
+                    self.set(x + tx, y + ty, op.apply(bottom, cell));
+                }
+            }
+        }
+    }
+
+    pub fn invert(&self) -> Self {
+        Self {
+            cells: self.cells.iter().map(|cell| cell.invert()).collect(),
+            ..self.clone()
+        }
+    }
+
+    /// mirror, left to right
+    pub fn flip_horizontal(&self) -> Self {
+        let (w, h) = (self.dots_width(), self.dots_height());
+        Self::from_dot_fn(w, h, |x, y| self.get_dot(w - 1 - x, y))
+    }
+
+    /// mirror, top to bottom
+    pub fn flip_vertical(&self) -> Self {
+        let (w, h) = (self.dots_width(), self.dots_height());
+        Self::from_dot_fn(w, h, |x, y| self.get_dot(x, h - 1 - y))
+    }
+
+    /// clockwise, the dots grid is rounded up to whole cells
+    pub fn rotate90(&self) -> Self {
+        let (w, h) = (self.dots_width(), self.dots_height());
+        Self::from_dot_fn(h, w, |x, y| self.get_dot(y, h - 1 - x))
+    }
+
+    pub fn rotate180(&self) -> Self {
+        let (w, h) = (self.dots_width(), self.dots_height());
+        Self::from_dot_fn(w, h, |x, y| self.get_dot(w - 1 - x, h - 1 - y))
+    }
+
+    /// counterclockwise, the dots grid is rounded up to whole cells
+    pub fn rotate270(&self) -> Self {
+        let (w, h) = (self.dots_width(), self.dots_height());
+        Self::from_dot_fn(h, w, |x, y| self.get_dot(w - 1 - y, x))
+    }
+
+    /// one pixel per dot: 255 when raised, 0 otherwise
+    pub fn to_bitmap(&self) -> GrayImage {
+        GrayImage::from_fn(
+            self.dots_width() as u32,
+            self.dots_height() as u32,
+            |x, y| match self.get_dot(x as usize, y as usize) {
+                true => Luma([255]),
+                false => Luma([0]),
+            },
+        )
+    }
+
+    /// one dot per pixel, raised when the pixel is at least `threshold`
+    pub fn from_bitmap(bitmap: &GrayImage, threshold: u8) -> Self {
+        Self::from_dot_fn(bitmap.width() as usize, bitmap.height() as usize, |x, y| {
+            bitmap.get_pixel(x as u32, y as u32).0[0] >= threshold
+        })
+    }
+}
+
+impl fmt::Display for BrailleImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut text = String::with_capacity((self.width + 1) * self.height * 3);
+        for (line_id, line) in self.lines().enumerate() {
+            if line_id > 0 {
+                text.push('\n');
+            }
+            text.extend(line.iter().map(|cell| cell.to_char()));
+        }
+        f.write_str(&text)
+    }
+}
+
+/// strict: every character must be a braille pattern, shorter lines are padded with empty cells
+impl FromStr for BrailleImage {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = s
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| BrailleCell::from_char(c).ok_or(CoreError::FailedToConvertToBraille))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::from_cells(lines))
+    }
+}
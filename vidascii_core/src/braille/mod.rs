@@ -0,0 +1,11 @@
+mod cell;
+mod decode;
+mod image;
+mod resample;
+
+pub use self::{
+    cell::{BrailleCell, CELL_HEIGHT, CELL_WIDTH},
+    decode::{decode, decode_to_bitmap},
+    image::{BoolOp, BrailleImage},
+    resample::{ResampleFilter, MAX_RESAMPLED_DOTS},
+};
@@ -0,0 +1,117 @@
+use super::{BrailleImage, CELL_HEIGHT, CELL_WIDTH};
+
+/// How new dots are created when enlarging, shrinking always uses coverage voting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResampleFilter {
+    /// each dot is a copy of the closest original dot, blocky
+    Nearest,
+    /// EPX/Scale2x: diagonal edges are smoothed instead of becoming stairs
+    Epx,
+}
+
+/// dots of the biggest art `BrailleImage::resample` makes, 10 million characters
+pub const MAX_RESAMPLED_DOTS: usize = 10_000_000 * CELL_WIDTH * CELL_HEIGHT;
+
+impl BrailleImage {
+    /// resize by `factor` (e.g. `2.0`, `0.5`, `1.3`), see `BrailleImage::resample`.
+    ///
+    /// `None` when `factor` is not a positive number or the art would have more than `MAX_RESAMPLED_DOTS` dots
+    pub fn scale(&self, factor: f32, filter: ResampleFilter) -> Option<Self> {
+        if !factor.is_finite() || factor <= 0.0 {
+            return None;
+        }
+        let scaled = |dots: usize| (dots as f64 * factor as f64).round().max(1.0);
+        let (dots_width, dots_height) = (scaled(self.dots_width()), scaled(self.dots_height()));
+        if dots_width * dots_height > MAX_RESAMPLED_DOTS as f64 {
+            return None;
+        }
+        self.resample(dots_width as usize, dots_height as usize, filter)
+    }
+
+    /// Resize the art directly at the dot level, without the original image.
+    ///
+    /// The target size is in dots and rounded up to whole cells.
+    /// Each new dot is raised if at least half of the area it covers in the original art is raised.
+    /// `None` when the target has more than `MAX_RESAMPLED_DOTS` dots
+    pub fn resample(
+        &self,
+        dots_width: usize,
+        dots_height: usize,
+        filter: ResampleFilter,
+    ) -> Option<Self> {
+        let (dots_width, dots_height) = (dots_width.max(1), dots_height.max(1));
+        if dots_width.saturating_mul(dots_height) > MAX_RESAMPLED_DOTS {
+            return None;
+        }
+        if self.dots_width() == 0 || self.dots_height() == 0 {
+            return Some(Self::from_dot_fn(dots_width, dots_height, |_, _| false));
+        }
+
+        let resampled = match filter {
+            ResampleFilter::Nearest => self.coverage_resample(dots_width, dots_height),
+            ResampleFilter::Epx => {
+                // upscale by 2 until big enough (or as big as allowed), then vote down to the exact size
+                let mut upscaled = self.clone();
+                while (upscaled.dots_width() < dots_width || upscaled.dots_height() < dots_height)
+                    && upscaled.dots_width() * upscaled.dots_height() * 4 <= MAX_RESAMPLED_DOTS
+                {
+                    upscaled = upscaled.scale2x();
+                }
+                upscaled.coverage_resample(dots_width, dots_height)
+            }
+        };
+        Some(resampled)
+    }
+
+    fn coverage_resample(&self, dots_width: usize, dots_height: usize) -> Self {
+        let (scale_x, scale_y) = (
+            self.dots_width() as f32 / dots_width as f32,
+            self.dots_height() as f32 / dots_height as f32,
+        );
+
+        Self::from_dot_fn(dots_width, dots_height, |x, y| {
+            let (x_start, x_end) = (x as f32 * scale_x, (x + 1) as f32 * scale_x);
+            let (y_start, y_end) = (y as f32 * scale_y, (y + 1) as f32 * scale_y);
+
+            let mut raised_area = 0.0;
+            for sy in y_start.floor() as usize..(y_end.ceil() as usize).min(self.dots_height()) {
+                let height = y_end.min(sy as f32 + 1.0) - y_start.max(sy as f32);
+                for sx in x_start.floor() as usize..(x_end.ceil() as usize).min(self.dots_width()) {
+                    if self.get_dot(sx, sy) {
+                        let width = x_end.min(sx as f32 + 1.0) - x_start.max(sx as f32);
+                        raised_area += width * height;
+                    }
+                }
+            }
+
+            raised_area >= 0.5 * scale_x * scale_y
+        })
+    }
+
+    /// https://en.wikipedia.org/wiki/Pixel-art_scaling_algorithms#EPX/Scale2×/AdvMAME2×
+    fn scale2x(&self) -> Self {
+        let (w, h) = (self.dots_width(), self.dots_height());
+        // out of bounds neighbors are the dot itself
+        let dot_or = |x: Option<usize>, y: Option<usize>, p: bool| match (x, y) {
+            (Some(x), Some(y)) if x < w && y < h => self.get_dot(x, y),
+            _ => p,
+        };
+
+        Self::from_dot_fn(w * 2, h * 2, |x, y| {
+            let (px, py) = (x / 2, y / 2);
+            let p = self.get_dot(px, py);
+            let a = dot_or(Some(px), py.checked_sub(1), p); // up
+            let b = dot_or(Some(px + 1), Some(py), p); // right
+            let c = dot_or(px.checked_sub(1), Some(py), p); // left
+            let d = dot_or(Some(px), Some(py + 1), p); // down
+
+            match (x % 2, y % 2) {
+                (0, 0) if c == a && c != d && a != b => a,
+                (1, 0) if a == b && a != c && b != d => b,
+                (0, 1) if d == c && d != b && c != a => c,
+                (1, 1) if b == d && b != a && d != c => d,
+                _ => p,
+            }
+        })
+    }
+}
@@ -0,0 +1,162 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use image::ImageBuffer;
+use imageproc::pixelops::weighted_sum;
+use rusttype::{point, Font, Scale};
+
+use crate::braille::{BrailleCell, BrailleImage};
+
+use super::{
+    config::Braille2ImgOptions,
+    font::{embedded_font, has_glyph, CustomFont},
+    layout::Grid,
+    RenderPixel,
+};
+
+/// The 256 braille glyphs rasterized once, drawn by blending their coverage like `draw_text_mut` does
+pub(crate) struct GlyphAtlas {
+    font: Option<CustomFont>,
+    font_size: f32,
+    glyphs: Vec<AtlasGlyph>,
+}
+
+/// covered pixels of a glyph: (x, y, coverage), relatively to the top-left corner of its cell
+struct AtlasGlyph(Vec<(i32, i32, f32)>);
+
+impl GlyphAtlas {
+    pub(crate) fn rasterize(font: &Font, fallback: Option<&Font>, font_size: f32) -> Self {
+        let scale = Scale::uniform(font_size);
+        let glyphs = (0..=u8::MAX)
+            .map(|pattern| {
+                let character = BrailleCell(pattern).to_char();
+                let font = match fallback {
+                    Some(fallback) if !has_glyph(font, character) => fallback,
+                    _ => font,
+                };
+
+                let v_metrics = font.v_metrics(scale);
+                let glyph = font
+                    .glyph(character)
+                    .scaled(scale)
+                    .positioned(point(0.0, v_metrics.ascent));
+                let mut covered = vec![];
+                if let Some(bb) = glyph.pixel_bounding_box() {
+                    glyph.draw(|x, y, value| {
+                        // blending a zero coverage leaves the pixel unchanged
+                        if value > 0.0 {
+                            covered.push((x as i32 + bb.min.x, y as i32 + bb.min.y, value));
+                        }
+                    });
+                }
+                AtlasGlyph(covered)
+            })
+            .collect();
+
+        Self {
+            font: None,
+            font_size,
+            glyphs,
+        }
+    }
+
+    /// whether it was rasterized with the font and size of `options`
+    fn matches(&self, options: &Braille2ImgOptions) -> bool {
+        let same_font = match (&self.font, &options.font) {
+            (None, None) => true,
+            (Some(font), Some(other)) => font.is_same(other),
+            _ => false,
+        };
+        same_font && self.font_size == options.font_size
+    }
+
+    /// blends `cell` glyph over `image`, its cell top-left corner being at (`x`, `y`)
+    fn blit<P: RenderPixel>(
+        &self,
+        image: &mut ImageBuffer<P, Vec<u8>>,
+        cell: BrailleCell,
+        x: i32,
+        y: i32,
+        color: P,
+    ) {
+        let (image_width, image_height) = (image.width() as i32, image.height() as i32);
+
+        for &(dx, dy, value) in &self.glyphs[cell.0 as usize].0 {
+            let (image_x, image_y) = (x + dx, y + dy);
+            if (0..image_width).contains(&image_x) && (0..image_height).contains(&image_y) {
+                let pixel = image.get_pixel_mut(image_x as u32, image_y as u32);
+                *pixel = match value >= 1.0 {
+                    true => color,
+                    false => weighted_sum(*pixel, color, 1.0 - value, value),
+                };
+            }
+        }
+    }
+}
+
+/// atlases kept by a cache, enough for the natural and the fitted font size of a few fonts
+const MAX_CACHED_ATLASES: usize = 4;
+
+/// Lazily built atlases, one per font and font size, shared by the clones of a `Braille2ImgOptions` (and so across threads)
+#[derive(Clone, Default)]
+pub(crate) struct GlyphAtlasCache(Arc<Mutex<Vec<Arc<GlyphAtlas>>>>);
+
+impl GlyphAtlasCache {
+    /// the atlas for the font and font size of `options`, rasterized on the first use
+    fn get(&self, options: &Braille2ImgOptions) -> Arc<GlyphAtlas> {
+        // the atlases are only pushed once rasterized, a poisoned list is still valid
+        let mut atlases = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(atlas) = atlases.iter().find(|atlas| atlas.matches(options)) {
+            return atlas.clone();
+        }
+
+        // rasterized while locked, so that the threads of a video wait for it instead of all building it
+        let atlas = Arc::new(rasterize_for(options));
+        if atlases.len() == MAX_CACHED_ATLASES {
+            atlases.remove(0);
+        }
+        atlases.push(atlas.clone());
+        atlas
+    }
+}
+
+impl fmt::Debug for GlyphAtlasCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cached = self.0.lock().map_or(0, |atlases| atlases.len());
+        f.debug_tuple("GlyphAtlasCache").field(&cached).finish()
+    }
+}
+
+fn rasterize_for(options: &Braille2ImgOptions) -> GlyphAtlas {
+    let embedded = embedded_font();
+    match options.font {
+        Some(ref custom) => GlyphAtlas {
+            font: Some(custom.clone()),
+            ..GlyphAtlas::rasterize(&custom.0, Some(&embedded), options.font_size)
+        },
+        None => GlyphAtlas::rasterize(&embedded, None, options.font_size),
+    }
+}
+
+/// Same output as drawing `art.to_string()` with `text_to_image`, without rasterizing each character
+pub(crate) fn render_with_atlas<P: RenderPixel>(
+    art: &BrailleImage,
+    options: &Braille2ImgOptions,
+) -> ImageBuffer<P, Vec<u8>> {
+    let atlas = options.atlas.get(options);
+
+    let grid = Grid::new(options);
+    let (width, height) = grid.size(art.width(), art.height());
+    let mut image = ImageBuffer::from_pixel(width, height, P::background(options));
+    let text_color = P::foreground(options);
+    for (char_y, line) in art.lines().enumerate() {
+        for (char_x, &cell) in line.iter().enumerate() {
+            let (x, y) = grid.origin(char_x, char_y);
+            atlas.blit(&mut image, cell, x as i32, y as i32, text_color);
+        }
+    }
+
+    image
+}
@@ -0,0 +1,35 @@
+use image::{imageops, DynamicImage, RgbaImage};
+
+use super::config::Comparison;
+
+/// `rendering` composed with `source` as `comparison`
+pub(crate) fn compose(
+    source: &DynamicImage,
+    rendering: &RgbaImage,
+    comparison: Comparison,
+) -> RgbaImage {
+    let source = source.to_rgba8();
+    match comparison {
+        Comparison::SideBySide => {
+            let (width, height) = source.dimensions();
+            let mut canvas =
+                RgbaImage::new(width + rendering.width(), height.max(rendering.height()));
+            imageops::replace(&mut canvas, &source, 0, 0);
+            imageops::replace(&mut canvas, rendering, width as i64, 0);
+            canvas
+        }
+        Comparison::Overlay(opacity) => {
+            let opacity = match opacity.is_nan() {
+                true => 1.0,
+                false => opacity.clamp(0.0, 1.0),
+            };
+            let mut layer = rendering.clone();
+            for pixel in layer.pixels_mut() {
+                pixel.0[3] = (pixel.0[3] as f32 * opacity).round() as u8;
+            }
+            let mut canvas = source;
+            imageops::overlay(&mut canvas, &layer, 0, 0);
+            canvas
+        }
+    }
+}
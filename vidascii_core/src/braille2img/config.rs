@@ -1,8 +1,188 @@
+use super::{
+    atlas::GlyphAtlasCache,
+    font::{braille_char_metrics, CustomFont},
+};
+
 const BASE_FONT_SIZE: f32 = 20.0; // 20px
-const BASE_CHAR_WIDTH: u32 = 10; // 10px
-const BASE_CHAR_HEIGHT: u32 = 18; // 18px
 
-#[allow(dead_code)]
+/// Shape of the dots drawn by `Renderer::Dots`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DotShape {
+    Circle,
+    Square,
+}
+
+/// Geometry of the dots drawn by `Renderer::Dots`, all in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DotStyle {
+    pub shape: DotShape,
+    pub diameter: u32,
+    /// gap between two dots of the same character
+    pub spacing: u32,
+    /// empty border around each character, two neighbor characters are `2 * cell_padding` apart
+    pub cell_padding: u32,
+}
+
+impl DotStyle {
+    pub fn cell_width(&self) -> u32 {
+        2 * self.cell_padding + 2 * self.diameter + self.spacing
+    }
+    pub fn cell_height(&self) -> u32 {
+        2 * self.cell_padding + 4 * self.diameter + 3 * self.spacing
+    }
+}
+
+impl Default for DotStyle {
+    fn default() -> Self {
+        Self {
+            shape: DotShape::Circle,
+            diameter: 3,
+            spacing: 1,
+            cell_padding: 1,
+        }
+    }
+}
+
+/// zlib effort of the png encoder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PngCompression {
+    Fast,
+    Default,
+    /// smallest files, slowest
+    #[default]
+    Best,
+}
+
+/// Encoding of the rendered image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png(PngCompression),
+    /// lossy, quality between 1 and 100
+    Jpeg(u8),
+    /// lossless
+    WebP,
+    Bmp,
+    /// RGBA pixels row by row without any header,
+    /// the size is the same as the encoded formats
+    RawRgba,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png(_) => "png",
+            ImageFormat::Jpeg(_) => "jpg",
+            ImageFormat::WebP => "webp",
+            ImageFormat::Bmp => "bmp",
+            ImageFormat::RawRgba => "rgba",
+        }
+    }
+    /// whether `transparent_background` is honored, jpeg has no alpha channel
+    pub fn supports_transparency(&self) -> bool {
+        !matches!(self, ImageFormat::Jpeg(_))
+    }
+}
+
+impl Default for ImageFormat {
+    fn default() -> Self {
+        ImageFormat::Png(PngCompression::default())
+    }
+}
+
+/// How the braille characters are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Renderer {
+    /// text rendering with the bundled braille font
+    #[default]
+    Font,
+    /// each dot is drawn as a shape, pixel-exact at any size and without font
+    Dots(DotStyle),
+}
+
+/// Pixel size of the rendered image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputSize {
+    /// `char_width` x `char_height` per character (plus the margins and spacings)
+    #[default]
+    Cells,
+    /// (width, height), the cells are scaled to fit then the image is resized to exactly this size
+    Pixels(u32, u32),
+    /// the size of the converted image, or the dot size of the braille art when rendering it directly
+    Source,
+}
+
+/// Source image and braille rendering composed together, to review a conversion.
+/// The rendering is sized like the source
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    /// source on the left, rendering on the right
+    SideBySide,
+    /// rendering blended over the source, with an opacity between 0 and 1
+    Overlay(f32),
+}
+
+/// Text color varying across the image, from `text_color` to the given color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gradient {
+    /// top to bottom
+    Vertical([u8; 3]),
+    /// left to right
+    Horizontal([u8; 3]),
+    /// center to corners
+    Radial([u8; 3]),
+}
+
+/// Retro CRT look, applied after drawing (raster formats only)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CrtEffect {
+    /// radius of the halo around the characters in pixels, 0 for none
+    pub glow: f32,
+    /// dims every other row of pixels
+    pub scanlines: bool,
+}
+
+impl CrtEffect {
+    pub fn is_none(&self) -> bool {
+        self.glow <= 0.0 && !self.scanlines
+    }
+}
+
+/// Ready-made colors (and effect)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    /// phosphor green on black, with glow and scanlines
+    TerminalGreen,
+    /// amber monochrome monitor, with glow and scanlines
+    AmberCrt,
+    /// ink on paper
+    Paper,
+    SolarizedLight,
+    SolarizedDark,
+}
+
+impl Theme {
+    /// (background, text) rgb values
+    pub fn colors(self) -> ([u8; 3], [u8; 3]) {
+        match self {
+            Theme::TerminalGreen => ([8, 16, 8], [51, 255, 102]),
+            Theme::AmberCrt => ([20, 12, 0], [255, 176, 0]),
+            Theme::Paper => ([245, 240, 225], [40, 36, 32]),
+            Theme::SolarizedLight => ([253, 246, 227], [101, 123, 131]),
+            Theme::SolarizedDark => ([0, 43, 54], [131, 148, 150]),
+        }
+    }
+    pub fn effect(self) -> CrtEffect {
+        match self {
+            Theme::TerminalGreen | Theme::AmberCrt => CrtEffect {
+                glow: 2.0,
+                scanlines: true,
+            },
+            Theme::Paper | Theme::SolarizedLight | Theme::SolarizedDark => CrtEffect::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Braille2ImgOptionsBuilder {
     /// rbg value
     background_color: [u8; 3],
@@ -10,9 +190,19 @@ pub struct Braille2ImgOptionsBuilder {
     text_color: [u8; 3],
     /// in pixels
     font_size: f32,
+    font: Option<CustomFont>,
+    renderer: Renderer,
+    format: ImageFormat,
+    transparent_background: bool,
+    margin: u32,
+    line_spacing: u32,
+    letter_spacing: u32,
+    gradient: Option<Gradient>,
+    effect: CrtEffect,
+    size: OutputSize,
+    comparison: Option<Comparison>,
 }
 
-#[allow(dead_code)]
 impl Braille2ImgOptionsBuilder {
     pub fn set_bg_color(mut self, rgb: [u8; 3]) -> Self {
         self.background_color = rgb;
@@ -26,16 +216,88 @@ impl Braille2ImgOptionsBuilder {
         self.font_size = size;
         self
     }
+    /// replaces the embedded braille font
+    pub fn set_font(mut self, font: CustomFont) -> Self {
+        self.font = Some(font);
+        self
+    }
+    /// with `Renderer::Dots`, the font size and font are ignored
+    pub fn set_renderer(mut self, renderer: Renderer) -> Self {
+        self.renderer = renderer;
+        self
+    }
+    pub fn set_format(mut self, format: ImageFormat) -> Self {
+        self.format = format;
+        self
+    }
+    /// only the glyphs (or dots) are drawn, ignored by the formats without alpha channel
+    pub fn set_transparent_background(mut self, transparent: bool) -> Self {
+        self.transparent_background = transparent;
+        self
+    }
+    /// empty border around the text, in pixels
+    pub fn set_margin(mut self, margin: u32) -> Self {
+        self.margin = margin;
+        self
+    }
+    /// extra pixels between two lines
+    pub fn set_line_spacing(mut self, spacing: u32) -> Self {
+        self.line_spacing = spacing;
+        self
+    }
+    /// extra pixels between two characters
+    pub fn set_letter_spacing(mut self, spacing: u32) -> Self {
+        self.letter_spacing = spacing;
+        self
+    }
+    pub fn set_gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = Some(gradient);
+        self
+    }
+    pub fn set_effect(mut self, effect: CrtEffect) -> Self {
+        self.effect = effect;
+        self
+    }
+    pub fn set_size(mut self, size: OutputSize) -> Self {
+        self.size = size;
+        self
+    }
+    /// only used by the conversions, which know the source image
+    pub fn set_comparison(mut self, comparison: Comparison) -> Self {
+        self.comparison = Some(comparison);
+        self
+    }
+    /// overrides `background_color`, `text_color` and `effect`
+    pub fn set_theme(self, theme: Theme) -> Self {
+        let (background_color, text_color) = theme.colors();
+        self.set_bg_color(background_color)
+            .set_text_color(text_color)
+            .set_effect(theme.effect())
+    }
     pub fn build(self) -> Braille2ImgOptions {
-        let char_width = self.font_size * BASE_CHAR_WIDTH as f32 / BASE_FONT_SIZE;
-        let char_height = self.font_size * BASE_CHAR_HEIGHT as f32 / BASE_FONT_SIZE;
+        let (char_width, char_height) = match self.renderer {
+            Renderer::Font => braille_char_metrics(self.font.as_ref(), self.font_size),
+            Renderer::Dots(style) => (style.cell_width(), style.cell_height()),
+        };
 
         Braille2ImgOptions {
             background_color: self.background_color,
             text_color: self.text_color,
             font_size: self.font_size,
-            char_width: char_width.round() as u32,
-            char_height: char_height.round() as u32,
+            char_width,
+            char_height,
+            font: self.font,
+            renderer: self.renderer,
+            format: self.format,
+            transparent_background: self.transparent_background,
+            margin: self.margin,
+            line_spacing: self.line_spacing,
+            letter_spacing: self.letter_spacing,
+            gradient: self.gradient,
+            effect: self.effect,
+            size: self.size,
+            comparison: self.comparison,
+            atlas: GlyphAtlasCache::default(),
         }
     }
 }
@@ -45,12 +307,24 @@ impl Default for Braille2ImgOptionsBuilder {
         Self {
             background_color: [0, 0, 0],
             text_color: [255, 255, 255],
-            font_size: 20.0,
+            font_size: BASE_FONT_SIZE,
+            font: None,
+            renderer: Renderer::Font,
+            format: ImageFormat::default(),
+            transparent_background: false,
+            margin: 0,
+            line_spacing: 0,
+            letter_spacing: 0,
+            gradient: None,
+            effect: CrtEffect::default(),
+            size: OutputSize::default(),
+            comparison: None,
         }
     }
 }
 
 /// use: `Braille2ImgOptions::builder`
+#[derive(Debug, Clone)]
 pub struct Braille2ImgOptions {
     /// rbg value
     pub background_color: [u8; 3],
@@ -62,9 +336,30 @@ pub struct Braille2ImgOptions {
     pub char_width: u32,
     /// use: `Braille2ImgOptions::builder` if you don't know what this is
     pub char_height: u32,
+    /// `None` for the embedded braille font
+    pub font: Option<CustomFont>,
+    pub renderer: Renderer,
+    /// encoding of the functions returning bytes
+    pub format: ImageFormat,
+    /// `background_color` is replaced by full transparency, the glyphs edges keep their anti-aliasing in the alpha channel
+    pub transparent_background: bool,
+    /// in pixels, on every side
+    pub margin: u32,
+    /// in pixels, added to `char_height`
+    pub line_spacing: u32,
+    /// in pixels, added to `char_width`
+    pub letter_spacing: u32,
+    /// `None` for a plain `text_color`
+    pub gradient: Option<Gradient>,
+    pub effect: CrtEffect,
+    /// not applied to html
+    pub size: OutputSize,
+    /// used by `image_to_braille` and `video_to_braille`, ignored by svg and html
+    pub comparison: Option<Comparison>,
+    /// glyphs rasterized on the first render, shared with the clones of these options
+    pub(crate) atlas: GlyphAtlasCache,
 }
 
-#[allow(dead_code)]
 impl Braille2ImgOptions {
     pub fn builder() -> Braille2ImgOptionsBuilder {
         Braille2ImgOptionsBuilder::default()
@@ -73,12 +368,6 @@ impl Braille2ImgOptions {
 
 impl Default for Braille2ImgOptions {
     fn default() -> Self {
-        Self {
-            background_color: [0, 0, 0],
-            text_color: [255, 255, 255],
-            font_size: BASE_FONT_SIZE,
-            char_width: BASE_CHAR_WIDTH,
-            char_height: BASE_CHAR_HEIGHT,
-        }
+        Self::builder().build()
     }
 }
@@ -0,0 +1,68 @@
+use image::ImageBuffer;
+
+use crate::braille::{BrailleImage, CELL_HEIGHT, CELL_WIDTH};
+
+use super::{
+    config::{DotShape, DotStyle},
+    layout::Grid,
+    RenderPixel,
+};
+
+/// Draws each raised dot of `art` as a `style.shape`, the characters are laid out on `grid`
+/// (whose cells are `style.cell_width()` x `style.cell_height()`)
+pub(crate) fn render_dots<P: RenderPixel>(
+    art: &BrailleImage,
+    style: &DotStyle,
+    grid: &Grid,
+    background_color: P,
+    dot_color: P,
+) -> ImageBuffer<P, Vec<u8>> {
+    let (width, height) = grid.size(art.width(), art.height());
+    let mut image = ImageBuffer::from_pixel(width, height, background_color);
+
+    let mask = dot_mask(style);
+    let pitch = style.diameter + style.spacing;
+    for (char_y, line) in art.lines().enumerate() {
+        for (char_x, cell) in line.iter().enumerate() {
+            let (cell_x, cell_y) = grid.origin(char_x, char_y);
+            for inner_x in 0..CELL_WIDTH {
+                for inner_y in 0..CELL_HEIGHT {
+                    if !cell.get(inner_x, inner_y) {
+                        continue;
+                    }
+
+                    let (left, top) = (
+                        cell_x + style.cell_padding + inner_x as u32 * pitch,
+                        cell_y + style.cell_padding + inner_y as u32 * pitch,
+                    );
+                    for (i, _) in mask.iter().enumerate().filter(|(_, &inside)| inside) {
+                        let (dx, dy) = (i as u32 % style.diameter, i as u32 / style.diameter);
+                        image.put_pixel(left + dx, top + dy, dot_color);
+                    }
+                }
+            }
+        }
+    }
+
+    image
+}
+
+/// row-major `diameter`x`diameter` mask, a pixel belongs to a circle when its center is inside it.
+/// The radius is shrunk by a quarter pixel, otherwise a 3px circle would be a full square
+fn dot_mask(style: &DotStyle) -> Vec<bool> {
+    let diameter = style.diameter;
+    let radius = diameter as f32 / 2.0;
+    let inner_radius = radius - 0.25;
+    (0..diameter * diameter)
+        .map(|i| match style.shape {
+            DotShape::Square => true,
+            DotShape::Circle => {
+                let (dx, dy) = (
+                    (i % diameter) as f32 + 0.5 - radius,
+                    (i / diameter) as f32 + 0.5 - radius,
+                );
+                dx * dx + dy * dy <= inner_radius * inner_radius
+            }
+        })
+        .collect()
+}
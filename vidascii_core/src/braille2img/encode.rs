@@ -0,0 +1,61 @@
+use std::error::Error;
+
+use image::{
+    codecs::{
+        bmp::BmpEncoder,
+        jpeg::JpegEncoder,
+        png::{CompressionType, FilterType, PngEncoder},
+    },
+    DynamicImage,
+};
+
+use super::config::{ImageFormat, PngCompression};
+
+/// `image` is expected to be rgb8 or rgba8, jpeg drops the alpha channel
+pub(crate) fn encode(image: &DynamicImage, format: ImageFormat) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut img_bytes: Vec<u8> = Vec::new();
+    match format {
+        ImageFormat::Png(compression) => {
+            let (compression, filter) = match compression {
+                PngCompression::Fast => (CompressionType::Fast, FilterType::NoFilter),
+                PngCompression::Default => (CompressionType::Default, FilterType::Adaptive),
+                PngCompression::Best => (CompressionType::Best, FilterType::NoFilter),
+            };
+            let encoder = PngEncoder::new_with_quality(&mut img_bytes, compression, filter);
+            image.write_with_encoder(encoder)?;
+
+            /* Better png optimizer but overkill in this situation, so I removed it
+            let optimized_img_bytes = oxipng::optimize_from_memory(&img_bytes, &oxipng::Options::from_preset(2))?;
+            */
+        }
+        ImageFormat::Jpeg(quality) => {
+            JpegEncoder::new_with_quality(&mut img_bytes, quality.clamp(1, 100))
+                .encode_image(&image.to_rgb8())?;
+        }
+        ImageFormat::WebP => {
+            let color_type = match image.color().has_alpha() {
+                true => image_webp::ColorType::Rgba8,
+                false => image_webp::ColorType::Rgb8,
+            };
+            image_webp::WebPEncoder::new(&mut img_bytes).encode(
+                image.as_bytes(),
+                image.width(),
+                image.height(),
+                color_type,
+            )?;
+        }
+        ImageFormat::Bmp => {
+            BmpEncoder::new(&mut img_bytes).encode(
+                image.as_bytes(),
+                image.width(),
+                image.height(),
+                image.color(),
+            )?;
+        }
+        ImageFormat::RawRgba => {
+            img_bytes = image.to_rgba8().into_raw();
+        }
+    }
+
+    Ok(img_bytes)
+}
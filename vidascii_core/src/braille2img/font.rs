@@ -0,0 +1,82 @@
+use std::{fmt, fs, path::Path, sync::Arc};
+
+use rusttype::{Font, Scale};
+
+use crate::CoreError;
+
+/// character used to measure the braille fonts, all braille characters have the same advance
+const REFERENCE_CHAR: char = '⣿';
+
+/// A user-supplied TTF/OTF font, characters it lacks are drawn with the embedded braille font
+#[derive(Clone)]
+pub struct CustomFont(pub(crate) Font<'static>, Arc<[u8]>);
+
+impl CustomFont {
+    pub fn from_bytes(font_data: Vec<u8>) -> Result<Self, CoreError> {
+        let data = Arc::from(font_data.as_slice());
+        Font::try_from_vec(font_data)
+            .map(|font| Self(font, data))
+            .ok_or(CoreError::FailedToLoadFont)
+    }
+    /// the TTF/OTF file content
+    pub fn data(&self) -> &[u8] {
+        &self.1
+    }
+    pub fn from_path(path: &Path) -> Result<Self, CoreError> {
+        let font_data = fs::read(path).map_err(|_| CoreError::FileNotFound)?;
+        Self::from_bytes(font_data)
+    }
+    /// whether both are clones of the same loaded font
+    pub(crate) fn is_same(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.1, &other.1)
+    }
+}
+
+impl fmt::Debug for CustomFont {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomFont")
+            .field("glyph_count", &self.0.glyph_count())
+            .finish()
+    }
+}
+
+pub(crate) const EMBEDDED_FONT_DATA: &[u8] = include_bytes!("../../fonts/Braille.ttf");
+
+pub(crate) fn embedded_font() -> Font<'static> {
+    Font::try_from_bytes(EMBEDDED_FONT_DATA).expect("the embedded braille font is valid")
+}
+
+pub(crate) fn has_glyph(font: &Font, character: char) -> bool {
+    font.glyph(character).id().0 != 0
+}
+
+/// (advance width of `reference`, line height) in pixels
+pub(crate) fn char_metrics(font: &Font, reference: char, font_size: f32) -> (u32, u32) {
+    let scale = Scale::uniform(font_size);
+    let v_metrics = font.v_metrics(scale);
+    let advance_width = font
+        .glyph(reference)
+        .scaled(scale)
+        .h_metrics()
+        .advance_width;
+
+    (
+        advance_width.round() as u32,
+        (v_metrics.ascent - v_metrics.descent + v_metrics.line_gap).round() as u32,
+    )
+}
+
+/// cell size of braille characters drawn with `font`, measured on the embedded font when `font` has no braille
+pub(crate) fn braille_char_metrics(font: Option<&CustomFont>, font_size: f32) -> (u32, u32) {
+    let embedded = embedded_font();
+    match font {
+        Some(CustomFont(font, _)) if has_glyph(font, REFERENCE_CHAR) => {
+            char_metrics(font, REFERENCE_CHAR, font_size)
+        }
+        Some(CustomFont(font, _)) => (
+            char_metrics(&embedded, REFERENCE_CHAR, font_size).0,
+            char_metrics(font, REFERENCE_CHAR, font_size).1,
+        ),
+        None => char_metrics(&embedded, REFERENCE_CHAR, font_size),
+    }
+}
@@ -0,0 +1,100 @@
+use std::fmt::Write;
+
+use crate::braille::{BrailleCell, BrailleImage};
+
+use super::{
+    config::Braille2ImgOptions,
+    font::EMBEDDED_FONT_DATA,
+    svg::{data_url, font_face, hex_color, FALLBACK_FONT_FAMILY, FONT_FAMILY},
+};
+
+/// used when the embedded fonts are blocked, all have braille glyphs
+const FONT_STACK: &str =
+    r#""DejaVu Sans Mono","Segoe UI Symbol","Noto Sans Symbols 2",Menlo,Consolas,monospace"#;
+
+/// Writes `art` as a self-contained HTML page (the fonts are inlined), inside a `<pre>` block
+/// whose line height keeps the cell aspect of the png output.
+///
+/// `colors[line][column]` is the color of each character (see `img2braille::cell_colors`),
+/// adjacent characters of the same color share a `<span>`. Without it, the text is `text_color`
+pub fn braille_art_to_html(
+    art: &BrailleImage,
+    colors: Option<&[Vec<[u8; 3]>]>,
+    options: Option<Braille2ImgOptions>,
+) -> String {
+    let options = options.unwrap_or_default();
+
+    let mut font_faces = String::new();
+    if let Some(ref custom) = options.font {
+        font_faces.push_str(&font_face(FONT_FAMILY, &data_url(custom.data())));
+    }
+    let family = match options.font {
+        Some(_) => FALLBACK_FONT_FAMILY,
+        None => FONT_FAMILY,
+    };
+    font_faces.push_str(&font_face(family, &data_url(EMBEDDED_FONT_DATA)));
+    let background = match options.transparent_background {
+        true => String::new(),
+        false => format!("html{{background:{}}}", hex_color(options.background_color)),
+    };
+
+    let mut html = String::new();
+    html.push_str(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>vidascii</title>\n",
+    );
+    let _ = writeln!(
+        html,
+        "<style>{font_faces}{background}body{{margin:0}}pre{{margin:0;padding:{}px;font-family:{FONT_FAMILY},{FALLBACK_FONT_FAMILY},{FONT_STACK};font-size:{}px;line-height:{}px;letter-spacing:{}px;font-variant-ligatures:none;color:{}}}</style>",
+        options.margin,
+        options.font_size,
+        options.char_height + options.line_spacing,
+        options.letter_spacing,
+        hex_color(options.text_color)
+    );
+    html.push_str("</head>\n<body>\n<pre>");
+
+    for (line_id, line) in art.lines().enumerate() {
+        if line_id > 0 {
+            html.push('\n');
+        }
+        let row_colors = colors.and_then(|colors| colors.get(line_id));
+        for (color, text) in color_runs(line, row_colors, options.text_color) {
+            match color == options.text_color {
+                true => html.push_str(&text),
+                false => {
+                    let _ = write!(
+                        html,
+                        r#"<span style="color:{}">{text}</span>"#,
+                        hex_color(color)
+                    );
+                }
+            }
+        }
+    }
+
+    html.push_str("</pre>\n</body>\n</html>\n");
+    html
+}
+
+/// splits a line in runs of the same color, blank characters have no ink and join the current run
+fn color_runs(
+    line: &[BrailleCell],
+    colors: Option<&Vec<[u8; 3]>>,
+    text_color: [u8; 3],
+) -> Vec<([u8; 3], String)> {
+    let mut runs: Vec<([u8; 3], String)> = vec![];
+    for (column, &cell) in line.iter().enumerate() {
+        let color = match cell == BrailleCell::EMPTY {
+            true => runs.last().map_or(text_color, |(color, _)| *color),
+            false => colors
+                .and_then(|colors| colors.get(column))
+                .copied()
+                .unwrap_or(text_color),
+        };
+        match runs.last_mut() {
+            Some((run_color, text)) if *run_color == color => text.push(cell.to_char()),
+            _ => runs.push((color, cell.to_char().to_string())),
+        }
+    }
+    runs
+}
@@ -0,0 +1,115 @@
+use unicode_width::UnicodeWidthChar;
+
+use super::config::Braille2ImgOptions;
+
+/// columns between two tab stops
+const TAB_WIDTH: usize = 4;
+
+/// Pixel positions of the character cells, with the margins and spacings of the options
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Grid {
+    cell_width: u32,
+    cell_height: u32,
+    letter_spacing: u32,
+    line_spacing: u32,
+    margin: u32,
+}
+
+impl Grid {
+    pub(crate) fn new(options: &Braille2ImgOptions) -> Self {
+        Self {
+            cell_width: options.char_width,
+            cell_height: options.char_height,
+            letter_spacing: options.letter_spacing,
+            line_spacing: options.line_spacing,
+            margin: options.margin,
+        }
+    }
+
+    /// top-left corner of the cell at (`column`, `line`)
+    pub(crate) fn origin(&self, column: usize, line: usize) -> (u32, u32) {
+        (
+            self.margin + column as u32 * (self.cell_width + self.letter_spacing),
+            self.margin + line as u32 * (self.cell_height + self.line_spacing),
+        )
+    }
+
+    /// width of `columns` cells, letter spacing included
+    pub(crate) fn span(&self, columns: usize) -> u32 {
+        match columns {
+            0 => 0,
+            _ => columns as u32 * (self.cell_width + self.letter_spacing) - self.letter_spacing,
+        }
+    }
+
+    /// image size of a `columns` x `lines` text
+    pub(crate) fn size(&self, columns: usize, lines: usize) -> (u32, u32) {
+        let height = match lines {
+            0 => 0,
+            _ => lines as u32 * (self.cell_height + self.line_spacing) - self.line_spacing,
+        };
+        (
+            2 * self.margin + self.span(columns),
+            2 * self.margin + height,
+        )
+    }
+}
+
+/// A character placed on the grid, spanning `width` columns from `column`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PlacedChar {
+    pub(crate) character: char,
+    pub(crate) column: usize,
+    pub(crate) width: usize,
+}
+
+/// Text split in lines of placed characters, each character takes its display width
+/// (2 columns for CJK, 0 for combining marks which are drawn over the previous character)
+#[derive(Debug, Default)]
+pub(crate) struct TextLayout {
+    pub(crate) lines: Vec<Vec<PlacedChar>>,
+    /// columns of the widest line
+    pub(crate) columns: usize,
+}
+
+impl TextLayout {
+    pub(crate) fn new(text: &str) -> Self {
+        let mut layout = TextLayout::default();
+        for line in text.lines() {
+            let (mut placed, mut column) = (vec![], 0);
+            for character in line.chars() {
+                let width = match character {
+                    '\t' => TAB_WIDTH - column % TAB_WIDTH,
+                    _ => match character.width() {
+                        Some(width) => width,
+                        // control characters
+                        None => continue,
+                    },
+                };
+                match width {
+                    0 => {
+                        let previous = placed.last().map_or(0, |c: &PlacedChar| c.column);
+                        placed.push(PlacedChar {
+                            character,
+                            column: previous,
+                            width,
+                        });
+                    }
+                    _ => {
+                        if !character.is_whitespace() {
+                            placed.push(PlacedChar {
+                                character,
+                                column,
+                                width,
+                            });
+                        }
+                        column += width;
+                    }
+                }
+            }
+            layout.columns = layout.columns.max(column);
+            layout.lines.push(placed);
+        }
+        layout
+    }
+}
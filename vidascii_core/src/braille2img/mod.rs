@@ -1,69 +1,430 @@
+pub(crate) mod atlas;
+mod compare;
 pub mod config;
+mod dots;
+mod encode;
+pub mod font;
+pub mod html;
+pub(crate) mod layout;
+mod shading;
+pub(crate) mod sizing;
+pub mod svg;
 
 use std::error::Error;
 
-use config::Braille2ImgOptions;
-use image::{
-    codecs::png::{CompressionType, FilterType, PngEncoder},
-    ImageBuffer, Rgb, RgbImage,
-};
+use config::{Braille2ImgOptions, Comparison, OutputSize, Renderer};
+use image::{DynamicImage, ImageBuffer, Luma, Pixel, Rgb, RgbImage, Rgba, RgbaImage};
 use rusttype::{Font, Scale};
 
+use crate::braille::{decode, BrailleImage};
+
+use self::{
+    atlas::render_with_atlas,
+    compare::compose,
+    encode::encode,
+    font::{embedded_font, has_glyph},
+    layout::{Grid, TextLayout},
+    shading::{is_shaded, shade},
+    sizing::render_sized,
+};
+
+/// Pixels the renderers can draw on
+pub(crate) trait RenderPixel: Pixel<Subpixel = u8> + 'static {
+    fn background(options: &Braille2ImgOptions) -> Self;
+    fn foreground(options: &Braille2ImgOptions) -> Self;
+    /// `color` over the background, `coverage` being between 0 and 1
+    fn mix(options: &Braille2ImgOptions, color: [u8; 3], coverage: f32) -> Self;
+}
+
+impl RenderPixel for Rgb<u8> {
+    fn background(options: &Braille2ImgOptions) -> Self {
+        Rgb(options.background_color)
+    }
+    fn foreground(options: &Braille2ImgOptions) -> Self {
+        Rgb(options.text_color)
+    }
+    fn mix(options: &Braille2ImgOptions, color: [u8; 3], coverage: f32) -> Self {
+        let mut mixed = options.background_color;
+        for (channel, color) in mixed.iter_mut().zip(color) {
+            *channel = (*channel as f32 * (1.0 - coverage) + color as f32 * coverage).round() as u8;
+        }
+        Rgb(mixed)
+    }
+}
+
+/// only for transparent backgrounds, blending over an opaque alpha would round it down to 254
+impl RenderPixel for Rgba<u8> {
+    /// the text color, so that blending the glyphs edges only changes the alpha
+    fn background(options: &Braille2ImgOptions) -> Self {
+        let [r, g, b] = options.text_color;
+        Rgba([r, g, b, 0])
+    }
+    fn foreground(options: &Braille2ImgOptions) -> Self {
+        let [r, g, b] = options.text_color;
+        Rgba([r, g, b, 255])
+    }
+    fn mix(_: &Braille2ImgOptions, [r, g, b]: [u8; 3], coverage: f32) -> Self {
+        Rgba([r, g, b, (coverage * 255.0).round() as u8])
+    }
+}
+
+/// coverage mask of the text, shaded afterward
+impl RenderPixel for Luma<u8> {
+    fn background(_: &Braille2ImgOptions) -> Self {
+        Luma([0])
+    }
+    fn foreground(_: &Braille2ImgOptions) -> Self {
+        Luma([255])
+    }
+    fn mix(_: &Braille2ImgOptions, _: [u8; 3], coverage: f32) -> Self {
+        Luma([(coverage * 255.0).round() as u8])
+    }
+}
+
+/// Renders braille `text` (one braille art line per text line), encoded in `options.format`
 pub fn braille_to_image(
     text: &str,
     options: Option<Braille2ImgOptions>,
 ) -> Result<Vec<u8>, Box<dyn Error>> {
     let options = options.unwrap_or_default();
-    if text.trim().is_empty() {
-        return Err("Text is empty".into());
+    match has_alpha(&options) {
+        true => encode(
+            &DynamicImage::ImageRgba8(render_text(text, &options)?),
+            options.format,
+        ),
+        false => encode(
+            &DynamicImage::ImageRgb8(render_text(text, &options)?),
+            options.format,
+        ),
+    }
+}
+
+/// same as `braille_to_image`, without encoding (and so without transparency)
+pub fn braille_to_rgb_image(
+    text: &str,
+    options: Option<Braille2ImgOptions>,
+) -> Result<RgbImage, Box<dyn Error>> {
+    render_text(text, &options.unwrap_or_default())
+}
+
+/// same as `braille_to_image`, without encoding
+pub fn braille_to_rgba_image(
+    text: &str,
+    options: Option<Braille2ImgOptions>,
+) -> Result<RgbaImage, Box<dyn Error>> {
+    let options = options.unwrap_or_default();
+    match options.transparent_background {
+        true => render_text(text, &options),
+        false => Ok(DynamicImage::ImageRgb8(render_text(text, &options)?).into_rgba8()),
+    }
+}
+
+/// same as `braille_to_image`, from a braille grid
+pub fn braille_art_to_image(
+    art: &BrailleImage,
+    options: Option<Braille2ImgOptions>,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let options = options.unwrap_or_default();
+    match has_alpha(&options) {
+        true => encode(
+            &DynamicImage::ImageRgba8(render_art(art, &options)?),
+            options.format,
+        ),
+        false => encode(
+            &DynamicImage::ImageRgb8(render_art(art, &options)?),
+            options.format,
+        ),
+    }
+}
+
+/// same as `braille_art_to_image`, without encoding (and so without transparency)
+pub fn braille_art_to_rgb_image(
+    art: &BrailleImage,
+    options: Option<Braille2ImgOptions>,
+) -> Result<RgbImage, Box<dyn Error>> {
+    render_art(art, &options.unwrap_or_default())
+}
+
+/// same as `braille_art_to_image`, without encoding
+pub fn braille_art_to_rgba_image(
+    art: &BrailleImage,
+    options: Option<Braille2ImgOptions>,
+) -> Result<RgbaImage, Box<dyn Error>> {
+    let options = options.unwrap_or_default();
+    match options.transparent_background {
+        true => render_art(art, &options),
+        false => Ok(DynamicImage::ImageRgb8(render_art(art, &options)?).into_rgba8()),
+    }
+}
+
+/// `art` rendered at the size of `source` (the image it was converted from), then composed with it
+/// as `options.comparison` and encoded in `options.format`. Without comparison, same as `braille_art_to_image`
+pub fn braille_art_comparison_to_image(
+    source: &DynamicImage,
+    art: &BrailleImage,
+    options: Option<Braille2ImgOptions>,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let options = options.unwrap_or_default();
+    let Some(comparison) = options.comparison else {
+        return braille_art_to_image(art, Some(options));
+    };
+
+    let composed = render_comparison(source, art, &options, comparison)?;
+    match has_alpha(&options) {
+        true => encode(&DynamicImage::ImageRgba8(composed), options.format),
+        false => encode(
+            &DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(composed).into_rgb8()),
+            options.format,
+        ),
     }
+}
 
-    let lines = text.lines().collect::<Vec<_>>();
-    let (image_width, image_height) = (
-        options.char_width * lines[0].chars().count() as u32,
-        options.char_height * lines.len() as u32,
-    );
+/// same as `braille_art_comparison_to_image`, without encoding (and so without transparency)
+pub fn braille_art_comparison_to_rgb_image(
+    source: &DynamicImage,
+    art: &BrailleImage,
+    options: Option<Braille2ImgOptions>,
+) -> Result<RgbImage, Box<dyn Error>> {
+    let options = options.unwrap_or_default();
+    match options.comparison {
+        None => render_art(art, &options),
+        Some(comparison) => Ok(DynamicImage::ImageRgba8(render_comparison(
+            source, art, &options, comparison,
+        )?)
+        .into_rgb8()),
+    }
+}
 
-    // Create a new RGB image buffer
-    let mut image: RgbImage = ImageBuffer::new(image_width, image_height);
+fn render_comparison(
+    source: &DynamicImage,
+    art: &BrailleImage,
+    options: &Braille2ImgOptions,
+    comparison: Comparison,
+) -> Result<RgbaImage, Box<dyn Error>> {
+    let options = Braille2ImgOptions {
+        size: OutputSize::Pixels(source.width(), source.height()),
+        ..options.clone()
+    };
+    Ok(compose(
+        source,
+        &braille_art_to_rgba_image(art, Some(options))?,
+        comparison,
+    ))
+}
+
+/// whether the encoded output keeps an alpha channel
+fn has_alpha(options: &Braille2ImgOptions) -> bool {
+    options.transparent_background && options.format.supports_transparency()
+}
 
-    // Set the background color to black
-    let background_color = Rgb(options.background_color);
-    for pixel in image.pixels_mut() {
-        *pixel = background_color;
+fn render_text<P: RenderPixel>(
+    text: &str,
+    options: &Braille2ImgOptions,
+) -> Result<ImageBuffer<P, Vec<u8>>, Box<dyn Error>> {
+    render_sized(
+        options,
+        || text_dimensions(text, options),
+        |options| render_text_cells(text, options),
+    )
+}
+
+fn render_art<P: RenderPixel>(
+    art: &BrailleImage,
+    options: &Braille2ImgOptions,
+) -> Result<ImageBuffer<P, Vec<u8>>, Box<dyn Error>> {
+    render_sized(
+        options,
+        || (art.width(), art.height()),
+        |options| render_art_cells(art, options),
+    )
+}
+
+/// (columns, lines) of `text` once laid out by the renderer
+fn text_dimensions(text: &str, options: &Braille2ImgOptions) -> (usize, usize) {
+    let art = match options.renderer {
+        Renderer::Font => text.parse::<BrailleImage>().ok(),
+        Renderer::Dots(_) => Some(decode(text)),
+    };
+    match art {
+        Some(art) => (art.width(), art.height()),
+        None => {
+            let layout = TextLayout::new(text);
+            (layout.columns, layout.lines.len())
+        }
     }
+}
 
-    // Set the text color
-    let text_color = Rgb(options.text_color);
+fn render_text_cells<P: RenderPixel>(
+    text: &str,
+    options: &Braille2ImgOptions,
+) -> Result<ImageBuffer<P, Vec<u8>>, Box<dyn Error>> {
+    match is_shaded(options) {
+        true => Ok(shade(
+            &draw_braille_text::<Luma<u8>>(text, options)?,
+            options,
+        )),
+        false => draw_braille_text(text, options),
+    }
+}
 
-    // Set font
-    let font_size = options.font_size;
-    let font_data: &[u8] = include_bytes!("../../fonts/Braille.ttf");
-    let font: Font<'static> =
-        Font::try_from_bytes(font_data).ok_or("Failed to load braille font")?;
+fn render_art_cells<P: RenderPixel>(
+    art: &BrailleImage,
+    options: &Braille2ImgOptions,
+) -> Result<ImageBuffer<P, Vec<u8>>, Box<dyn Error>> {
+    match is_shaded(options) {
+        true => Ok(shade(&draw_art::<Luma<u8>>(art, options)?, options)),
+        false => draw_art(art, options),
+    }
+}
 
-    // Add the text to the image
-    for (line_id, line) in lines.iter().enumerate() {
-        imageproc::drawing::draw_text_mut(
-            &mut image,
-            text_color,
-            0,
-            (line_id as u32 * options.char_height) as i32,
-            Scale::uniform(font_size),
-            &font,
-            line,
-        );
+fn draw_braille_text<P: RenderPixel>(
+    text: &str,
+    options: &Braille2ImgOptions,
+) -> Result<ImageBuffer<P, Vec<u8>>, Box<dyn Error>> {
+    match options.renderer {
+        // only braille characters are in the glyph atlas
+        Renderer::Font => match text.parse::<BrailleImage>() {
+            Ok(art) => draw_art(&art, options),
+            Err(_) => {
+                // with the custom font if any, the embedded one drawing what it lacks
+                let embedded = embedded_font();
+                match options.font {
+                    Some(ref custom) => draw_text(text, options, &custom.0, Some(&embedded)),
+                    None => draw_text(text, options, &embedded, None),
+                }
+            }
+        },
+        Renderer::Dots(_) => draw_art(&decode(text), options),
     }
+}
+
+fn draw_art<P: RenderPixel>(
+    art: &BrailleImage,
+    options: &Braille2ImgOptions,
+) -> Result<ImageBuffer<P, Vec<u8>>, Box<dyn Error>> {
+    if art.width() == 0 || art.height() == 0 {
+        return Err("Text is empty".into());
+    }
+    match options.renderer {
+        Renderer::Font => {
+            check_font_metrics(options)?;
+            Ok(render_with_atlas(art, options))
+        }
+        Renderer::Dots(style) => {
+            if style.diameter == 0 {
+                return Err("Dot diameter must be positive".into());
+            }
+            Ok(dots::render_dots(
+                art,
+                &style,
+                &Grid::new(options),
+                P::background(options),
+                P::foreground(options),
+            ))
+        }
+    }
+}
+
+/// renders each lines of `text` with `font`, one column every `options.char_width` pixels
+/// (wide characters such as CJK take two). The characters missing from `font` are drawn with `fallback`
+pub(crate) fn text_to_image(
+    text: &str,
+    options: &Braille2ImgOptions,
+    font: &Font,
+    fallback: Option<&Font>,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    fn draw_shaded<P: RenderPixel>(
+        text: &str,
+        options: &Braille2ImgOptions,
+        font: &Font,
+        fallback: Option<&Font>,
+    ) -> Result<ImageBuffer<P, Vec<u8>>, Box<dyn Error>> {
+        let dimensions = || {
+            let layout = TextLayout::new(text);
+            (layout.columns, layout.lines.len())
+        };
+        render_sized(options, dimensions, |options| match is_shaded(options) {
+            true => Ok(shade(
+                &draw_text::<Luma<u8>>(text, options, font, fallback)?,
+                options,
+            )),
+            false => draw_text(text, options, font, fallback),
+        })
+    }
+
+    match has_alpha(options) {
+        true => encode(
+            &DynamicImage::ImageRgba8(draw_shaded(text, options, font, fallback)?),
+            options.format,
+        ),
+        false => encode(
+            &DynamicImage::ImageRgb8(draw_shaded(text, options, font, fallback)?),
+            options.format,
+        ),
+    }
+}
+
+/// same as `text_to_image`, without encoding
+pub(crate) fn draw_text<P: RenderPixel>(
+    text: &str,
+    options: &Braille2ImgOptions,
+    font: &Font,
+    fallback: Option<&Font>,
+) -> Result<ImageBuffer<P, Vec<u8>>, Box<dyn Error>> {
+    if text.trim().is_empty() {
+        return Err("Text is empty".into());
+    }
+    check_font_metrics(options)?;
 
-    // output the image datas
-    let mut img_bytes: Vec<u8> = Vec::new();
-    let encoder =
-        PngEncoder::new_with_quality(&mut img_bytes, CompressionType::Best, FilterType::NoFilter);
-    image.write_with_encoder(encoder)?;
+    let (layout, grid) = (TextLayout::new(text), Grid::new(options));
+    let (image_width, image_height) = grid.size(layout.columns, layout.lines.len());
 
-    /* Better png optimizer but overkill in this situation, so I removed it
-    let optimized_img_bytes = oxipng::optimize_from_memory(&img_bytes, &oxipng::Options::from_preset(2))?;
-    */
+    let mut image = ImageBuffer::from_pixel(image_width, image_height, P::background(options));
+    let text_color = P::foreground(options);
+    let scale = Scale::uniform(options.font_size);
 
-    Ok(img_bytes)
+    // Add the text to the image, each character in its own cell so that the rounded
+    // `char_width` does not drift from the glyph advance
+    let mut buffer = [0; 4];
+    for (line_id, line) in layout.lines.iter().enumerate() {
+        for placed in line {
+            let font = match fallback {
+                Some(fallback) if !has_glyph(font, placed.character) => fallback,
+                _ => font,
+            };
+            let (mut x, y) = grid.origin(placed.column, line_id);
+            // wide glyphs are centered on their columns
+            if placed.width > 1 {
+                let advance = font
+                    .glyph(placed.character)
+                    .scaled(scale)
+                    .h_metrics()
+                    .advance_width;
+                x += ((grid.span(placed.width) as f32 - advance) / 2.0)
+                    .max(0.0)
+                    .round() as u32;
+            }
+            imageproc::drawing::draw_text_mut(
+                &mut image,
+                text_color,
+                x as i32,
+                y as i32,
+                scale,
+                font,
+                placed.character.encode_utf8(&mut buffer),
+            );
+        }
+    }
+
+    Ok(image)
+}
+
+fn check_font_metrics(options: &Braille2ImgOptions) -> Result<(), Box<dyn Error>> {
+    if !options.font_size.is_finite() || options.font_size <= 0.0 {
+        return Err("Font size must be positive".into());
+    }
+    if options.char_width == 0 || options.char_height == 0 {
+        return Err("Font size is too small".into());
+    }
+    Ok(())
 }
@@ -0,0 +1,72 @@
+use image::{GrayImage, ImageBuffer};
+use imageproc::filter::gaussian_blur_f32;
+
+use super::{
+    config::{Braille2ImgOptions, Gradient},
+    RenderPixel,
+};
+
+/// the blurred coverage is boosted, otherwise the halo of thin dots is barely visible
+const GLOW_GAIN: f32 = 2.0;
+const GLOW_OPACITY: f32 = 0.6;
+/// brightness of the dimmed rows
+const SCANLINE_BRIGHTNESS: f32 = 0.6;
+
+/// whether the text has to be drawn as a coverage mask first
+pub(crate) fn is_shaded(options: &Braille2ImgOptions) -> bool {
+    options.gradient.is_some() || !options.effect.is_none()
+}
+
+/// Colors the text coverage `mask` with the gradient and effect of `options`
+pub(crate) fn shade<P: RenderPixel>(
+    mask: &GrayImage,
+    options: &Braille2ImgOptions,
+) -> ImageBuffer<P, Vec<u8>> {
+    let glow = match options.effect.glow {
+        glow if glow.is_finite() && glow > 0.0 => Some(gaussian_blur_f32(mask, glow)),
+        _ => None,
+    };
+    let (width, height) = mask.dimensions();
+
+    ImageBuffer::from_fn(width, height, |x, y| {
+        let mut coverage = mask.get_pixel(x, y).0[0] as f32 / 255.0;
+        if let Some(ref glow) = glow {
+            let halo = (glow.get_pixel(x, y).0[0] as f32 / 255.0 * GLOW_GAIN).min(1.0);
+            coverage += (1.0 - coverage) * halo * GLOW_OPACITY;
+        }
+        if options.effect.scanlines && y % 2 == 1 {
+            coverage *= SCANLINE_BRIGHTNESS;
+        }
+        P::mix(
+            options,
+            gradient_color(options, x, y, width, height),
+            coverage,
+        )
+    })
+}
+
+fn gradient_color(
+    options: &Braille2ImgOptions,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> [u8; 3] {
+    let progress = |position: u32, size: u32| position as f32 / (size.max(2) - 1) as f32;
+    let (to, t) = match options.gradient {
+        None => return options.text_color,
+        Some(Gradient::Vertical(to)) => (to, progress(y, height)),
+        Some(Gradient::Horizontal(to)) => (to, progress(x, width)),
+        Some(Gradient::Radial(to)) => {
+            let (center_x, center_y) = (width as f32 / 2.0, height as f32 / 2.0);
+            let (dx, dy) = (x as f32 + 0.5 - center_x, y as f32 + 0.5 - center_y);
+            (to, (dx.hypot(dy) / center_x.hypot(center_y)).min(1.0))
+        }
+    };
+
+    let mut color = options.text_color;
+    for (channel, to) in color.iter_mut().zip(to) {
+        *channel = (*channel as f32 + (to as f32 - *channel as f32) * t).round() as u8;
+    }
+    color
+}
@@ -0,0 +1,122 @@
+use std::error::Error;
+
+use image::{imageops, imageops::FilterType, ImageBuffer};
+
+use crate::braille::{CELL_HEIGHT, CELL_WIDTH};
+
+use super::{
+    config::{Braille2ImgOptions, DotStyle, OutputSize, Renderer},
+    layout::Grid,
+    RenderPixel,
+};
+
+/// `render` with the options fitted to `options.size`, `dimensions` being the (columns, lines) of the text
+pub(crate) fn render_sized<P: RenderPixel>(
+    options: &Braille2ImgOptions,
+    dimensions: impl FnOnce() -> (usize, usize),
+    render: impl FnOnce(&Braille2ImgOptions) -> Result<ImageBuffer<P, Vec<u8>>, Box<dyn Error>>,
+) -> Result<ImageBuffer<P, Vec<u8>>, Box<dyn Error>> {
+    if options.size == OutputSize::Cells {
+        return render(options);
+    }
+    let (columns, lines) = dimensions();
+    match target_size(options, columns, lines)? {
+        None => render(options),
+        Some(target) => Ok(resize_to(
+            render(&fit_options(options, columns, lines, target))?,
+            target,
+        )),
+    }
+}
+
+/// pixel size requested by `options.size` for a `columns` x `lines` text, `None` when it is the natural one
+pub(crate) fn target_size(
+    options: &Braille2ImgOptions,
+    columns: usize,
+    lines: usize,
+) -> Result<Option<(u32, u32)>, Box<dyn Error>> {
+    let target = match options.size {
+        OutputSize::Cells => return Ok(None),
+        OutputSize::Pixels(width, height) => (width, height),
+        OutputSize::Source => ((columns * CELL_WIDTH) as u32, (lines * CELL_HEIGHT) as u32),
+    };
+    if target.0 == 0 || target.1 == 0 {
+        return Err("Output size must be positive".into());
+    }
+
+    let natural = Grid::new(options).size(columns, lines);
+    Ok((target != natural).then_some(target))
+}
+
+/// `options` with the cells (and margins and spacings) scaled so that a `columns` x `lines` text
+/// is close to `target`, keeping the cell aspect
+fn fit_options(
+    options: &Braille2ImgOptions,
+    columns: usize,
+    lines: usize,
+    target: (u32, u32),
+) -> Braille2ImgOptions {
+    let (width, height) = Grid::new(options).size(columns, lines);
+    let scale = (target.0 as f32 / width.max(1) as f32).min(target.1 as f32 / height.max(1) as f32);
+    let scaled = |value: u32| (value as f32 * scale).round() as u32;
+
+    let (renderer, font_size, char_width, char_height) = match options.renderer {
+        Renderer::Font => (
+            Renderer::Font,
+            (options.font_size * scale).max(1.0),
+            scaled(options.char_width).max(1),
+            scaled(options.char_height).max(1),
+        ),
+        Renderer::Dots(style) => {
+            let style = DotStyle {
+                diameter: scaled(style.diameter).max(1),
+                spacing: scaled(style.spacing),
+                cell_padding: scaled(style.cell_padding),
+                ..style
+            };
+            (
+                Renderer::Dots(style),
+                options.font_size,
+                style.cell_width(),
+                style.cell_height(),
+            )
+        }
+    };
+
+    Braille2ImgOptions {
+        font_size,
+        char_width,
+        char_height,
+        renderer,
+        margin: scaled(options.margin),
+        line_spacing: scaled(options.line_spacing),
+        letter_spacing: scaled(options.letter_spacing),
+        size: OutputSize::Cells,
+        ..options.clone()
+    }
+}
+
+/// `options` where `OutputSize::Source` is the size of the converted image
+pub(crate) fn with_source_size(
+    options: Option<Braille2ImgOptions>,
+    (width, height): (u32, u32),
+) -> Option<Braille2ImgOptions> {
+    match options {
+        Some(options) if options.size == OutputSize::Source => Some(Braille2ImgOptions {
+            size: OutputSize::Pixels(width, height),
+            ..options
+        }),
+        options => options,
+    }
+}
+
+/// resizes the rendering of the fitted options to exactly `target`
+fn resize_to<P: RenderPixel>(
+    image: ImageBuffer<P, Vec<u8>>,
+    target: (u32, u32),
+) -> ImageBuffer<P, Vec<u8>> {
+    match image.dimensions() == target {
+        true => image,
+        false => imageops::resize(&image, target.0, target.1, FilterType::Triangle),
+    }
+}
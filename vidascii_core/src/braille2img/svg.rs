@@ -0,0 +1,226 @@
+use std::fmt::Write;
+
+use rusttype::Scale;
+
+use crate::{
+    braille::{BrailleImage, CELL_HEIGHT, CELL_WIDTH},
+    utils::base64_encode,
+};
+
+use super::{
+    config::{Braille2ImgOptions, DotShape, Gradient, Renderer},
+    font::{embedded_font, EMBEDDED_FONT_DATA},
+    layout::Grid,
+    sizing::target_size,
+};
+
+pub(super) const FONT_FAMILY: &str = "vidascii-braille";
+pub(super) const FALLBACK_FONT_FAMILY: &str = "vidascii-braille-fallback";
+const GRADIENT_ID: &str = "vidascii-text";
+
+/// Where the SVG `<text>` rows get their font from
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SvgFont {
+    /// the font (and the embedded braille font as fallback) is inlined in the SVG, bigger but self-contained
+    #[default]
+    Embed,
+    /// the font is loaded from this URL by the viewer
+    Link(String),
+}
+
+/// Writes `art` as an SVG of the same size as the png output:
+/// - `Renderer::Font`: one `<text>` per line, stretched to exactly `char_width` per character
+/// - `Renderer::Dots`: one `<circle>` (or `<rect>`) per raised dot
+///
+/// Gradients are kept, the `CrtEffect` is not
+pub fn braille_art_to_svg(
+    art: &BrailleImage,
+    options: Option<Braille2ImgOptions>,
+    font: SvgFont,
+) -> String {
+    let options = options.unwrap_or_default();
+    let (width, height) = Grid::new(&options).size(art.width(), art.height());
+    // scaled by the viewer, a zero size is ignored
+    let (output_width, output_height) = target_size(&options, art.width(), art.height())
+        .ok()
+        .flatten()
+        .unwrap_or((width, height));
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{output_width}" height="{output_height}" viewBox="0 0 {width} {height}" preserveAspectRatio="none">"#
+    );
+    if !options.transparent_background {
+        let _ = writeln!(
+            svg,
+            r#"<rect width="100%" height="100%" fill="{}"/>"#,
+            hex_color(options.background_color)
+        );
+    }
+    if let Some(gradient) = options.gradient {
+        write_gradient(&mut svg, gradient, options.text_color, width, height);
+    }
+    match options.renderer {
+        Renderer::Font => write_text_rows(&mut svg, art, &options, &font),
+        Renderer::Dots(_) => write_dots(&mut svg, art, &options),
+    }
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+fn write_text_rows(
+    svg: &mut String,
+    art: &BrailleImage,
+    options: &Braille2ImgOptions,
+    font: &SvgFont,
+) {
+    let mut font_faces = String::new();
+    match font {
+        SvgFont::Embed => {
+            if let Some(ref custom) = options.font {
+                font_faces.push_str(&font_face(FONT_FAMILY, &data_url(custom.data())));
+            }
+            let family = match options.font {
+                Some(_) => FALLBACK_FONT_FAMILY,
+                None => FONT_FAMILY,
+            };
+            font_faces.push_str(&font_face(family, &data_url(EMBEDDED_FONT_DATA)));
+        }
+        SvgFont::Link(url) => font_faces.push_str(&font_face(FONT_FAMILY, &escape_url(url))),
+    }
+    let _ = writeln!(
+        svg,
+        "<style>{font_faces}text{{font-family:{FONT_FAMILY},{FALLBACK_FONT_FAMILY},monospace;font-size:{}px;fill:{};white-space:pre}}</style>",
+        options.font_size,
+        text_fill(options)
+    );
+
+    // baseline, like the png renderer which draws from the top of the ascent
+    let ascent = match options.font {
+        Some(ref custom) => custom.0.v_metrics(Scale::uniform(options.font_size)).ascent,
+        None => {
+            embedded_font()
+                .v_metrics(Scale::uniform(options.font_size))
+                .ascent
+        }
+    };
+    // the letter spacing after the last character keeps every advance equal
+    let grid = Grid::new(options);
+    let row_width = grid.span(art.width()) + options.letter_spacing;
+    for (line_id, line) in art.lines().enumerate() {
+        let text = line.iter().map(|cell| cell.to_char()).collect::<String>();
+        let (x, y) = grid.origin(0, line_id);
+        let _ = writeln!(
+            svg,
+            r#"<text x="{x}" y="{}" textLength="{row_width}" lengthAdjust="spacing">{text}</text>"#,
+            y as f32 + ascent
+        );
+    }
+}
+
+fn write_dots(svg: &mut String, art: &BrailleImage, options: &Braille2ImgOptions) {
+    let Renderer::Dots(style) = options.renderer else {
+        return;
+    };
+    let _ = writeln!(svg, r#"<g fill="{}">"#, text_fill(options));
+
+    let grid = Grid::new(options);
+    let (pitch, radius) = (style.diameter + style.spacing, style.diameter as f32 / 2.0);
+    for (char_y, line) in art.lines().enumerate() {
+        for (char_x, cell) in line.iter().enumerate() {
+            let (cell_x, cell_y) = grid.origin(char_x, char_y);
+            for inner_x in 0..CELL_WIDTH {
+                for inner_y in 0..CELL_HEIGHT {
+                    if !cell.get(inner_x, inner_y) {
+                        continue;
+                    }
+                    let (left, top) = (
+                        cell_x + style.cell_padding + inner_x as u32 * pitch,
+                        cell_y + style.cell_padding + inner_y as u32 * pitch,
+                    );
+                    let _ = match style.shape {
+                        DotShape::Circle => writeln!(
+                            svg,
+                            r#"<circle cx="{}" cy="{}" r="{radius}"/>"#,
+                            left as f32 + radius,
+                            top as f32 + radius
+                        ),
+                        DotShape::Square => writeln!(
+                            svg,
+                            r#"<rect x="{left}" y="{top}" width="{0}" height="{0}"/>"#,
+                            style.diameter
+                        ),
+                    };
+                }
+            }
+        }
+    }
+
+    svg.push_str("</g>\n");
+}
+
+pub(super) fn font_face(family: &str, url: &str) -> String {
+    format!("@font-face{{font-family:{family};src:url(\"{url}\")}}")
+}
+
+pub(super) fn data_url(font_data: &[u8]) -> String {
+    let mime = match font_data.starts_with(b"OTTO") {
+        true => "font/otf",
+        false => "font/ttf",
+    };
+    format!("data:{mime};base64,{}", base64_encode(font_data))
+}
+
+fn write_gradient(svg: &mut String, gradient: Gradient, from: [u8; 3], width: u32, height: u32) {
+    let (element, geometry, to) = match gradient {
+        Gradient::Vertical(to) => (
+            "linearGradient",
+            format!(r#"x1="0" y1="0" x2="0" y2="{height}""#),
+            to,
+        ),
+        Gradient::Horizontal(to) => (
+            "linearGradient",
+            format!(r#"x1="0" y1="0" x2="{width}" y2="0""#),
+            to,
+        ),
+        // reaching the corners, like the raster renderer
+        Gradient::Radial(to) => (
+            "radialGradient",
+            format!(
+                r#"cx="{}" cy="{}" r="{}""#,
+                width as f32 / 2.0,
+                height as f32 / 2.0,
+                (width as f32).hypot(height as f32) / 2.0
+            ),
+            to,
+        ),
+    };
+    // in image coordinates, not relative to each text row or dot
+    let _ = writeln!(
+        svg,
+        r#"<defs><{element} id="{GRADIENT_ID}" gradientUnits="userSpaceOnUse" {geometry}><stop offset="0" stop-color="{}"/><stop offset="1" stop-color="{}"/></{element}></defs>"#,
+        hex_color(from),
+        hex_color(to)
+    );
+}
+
+fn text_fill(options: &Braille2ImgOptions) -> String {
+    match options.gradient {
+        Some(_) => format!("url(#{GRADIENT_ID})"),
+        None => hex_color(options.text_color),
+    }
+}
+
+pub(super) fn hex_color([r, g, b]: [u8; 3]) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// the URL ends up in a CSS string, itself in the XML text of `<style>`
+fn escape_url(url: &str) -> String {
+    url.replace('"', "%22")
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
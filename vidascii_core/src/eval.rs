@@ -0,0 +1,242 @@
+use image::{
+    imageops::{self, FilterType},
+    DynamicImage, GrayImage,
+};
+use imageproc::filter::gaussian_blur_f32;
+
+use crate::{
+    braille::BrailleImage,
+    img2braille::{
+        config::{DitheringAlgorithm, Img2BrailleOptions, PatternSelection},
+        dynamic_image_to_braille_art, GrayScaleMode,
+    },
+    utils::{decode_image, to_grayscale},
+    CoreError,
+};
+
+/// in dots, how much both images are blurred before being compared, to mimic the eye merging neighbor dots
+const BLUR_SIGMA: f32 = 1.0;
+/// SSIM is computed on windows of `SSIM_WINDOW`x`SSIM_WINDOW` dots, every `SSIM_STEP` dots
+const SSIM_WINDOW: usize = 8;
+const SSIM_STEP: usize = 4;
+const SSIM_C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+const SSIM_C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+/// How close a braille art looks to its source image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QualityReport {
+    /// in dB, higher is better (`f64::INFINITY` for identical images)
+    pub psnr: f64,
+    /// between -1.0 and 1.0, higher is better
+    pub ssim: f64,
+    /// art mean brightness minus source mean brightness, between -1.0 (art too dark) and 1.0 (art too bright)
+    pub mean_brightness_error: f64,
+}
+
+/// Compares `art` with `source`: the art is decoded to one pixel per dot, the source is resized to the same size,
+/// then both are blurred before computing the metrics
+pub fn evaluate(source: &DynamicImage, art: &BrailleImage) -> QualityReport {
+    let art_bitmap = art.to_bitmap();
+    let source_bitmap = imageops::resize(
+        &to_grayscale(source),
+        art_bitmap.width().max(1),
+        art_bitmap.height().max(1),
+        FilterType::Triangle,
+    );
+
+    compare(
+        &gaussian_blur_f32(&source_bitmap, BLUR_SIGMA),
+        &gaussian_blur_f32(&art_bitmap, BLUR_SIGMA),
+    )
+}
+
+/// converts the image with `options` and evaluates the result
+pub fn evaluate_image(
+    image_bytes: &[u8],
+    options: Option<Img2BrailleOptions>,
+) -> Result<QualityReport, CoreError> {
+    let source = decode_image(image_bytes)?;
+    let art = dynamic_image_to_braille_art(source.clone(), options, None::<fn(&'static str, u8)>)?;
+    Ok(evaluate(&source, &art))
+}
+
+/// thresholds, gammas and grayscale modes tried by `find_best_options`
+const SEARCH_THRESHOLDS: [u8; 3] = [96, 128, 160];
+const SEARCH_GAMMAS: [f32; 3] = [0.8, 1.0, 1.25];
+const SEARCH_GRAYSCALES: [GrayScaleMode; 2] = [GrayScaleMode::Luminance, GrayScaleMode::Average];
+
+/// Tries combinations of threshold, dithering algorithm, gamma and grayscale mode at the given `ratio`
+/// and returns the options giving the best SSIM, along with their report
+pub fn find_best_options(
+    source: &DynamicImage,
+    ratio: f32,
+) -> Result<(Img2BrailleOptions, QualityReport), CoreError> {
+    let candidates = search_candidates(ratio);
+    let workers = std::thread::available_parallelism().map_or(1, |count| count.get());
+    let chunk_size = candidates.len().div_ceil(workers);
+
+    let results = std::thread::scope(|scope| {
+        let handles = candidates
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|&options| {
+                            let art = dynamic_image_to_braille_art(
+                                source.clone(),
+                                Some(options),
+                                None::<fn(&'static str, u8)>,
+                            )?;
+                            Ok((options, evaluate(source, &art)))
+                        })
+                        .collect::<Result<Vec<_>, CoreError>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .map_err(|_| CoreError::FailedToConvertToBraille)?
+            })
+            .collect::<Result<Vec<_>, CoreError>>()
+    })?;
+
+    results
+        .into_iter()
+        .flatten()
+        .max_by(|(_, a), (_, b)| a.ssim.total_cmp(&b.ssim))
+        .ok_or(CoreError::FailedToConvertToBraille)
+}
+
+/// same as `find_best_options`, from encoded image bytes
+pub fn find_best_options_for_image(
+    image_bytes: &[u8],
+    ratio: f32,
+) -> Result<(Img2BrailleOptions, QualityReport), CoreError> {
+    find_best_options(&decode_image(image_bytes)?, ratio)
+}
+
+/// threshold selection with every threshold and dithering, error-minimizing selection only varies the tone
+fn search_candidates(ratio: f32) -> Vec<Img2BrailleOptions> {
+    let mut candidates = Vec::new();
+    for gamma in SEARCH_GAMMAS {
+        for grayscale in SEARCH_GRAYSCALES {
+            let builder = Img2BrailleOptions::builder()
+                .set_ratio(ratio)
+                .set_gamma(gamma)
+                .set_grayscale(grayscale);
+
+            for threshold in SEARCH_THRESHOLDS {
+                let builder = builder
+                    .set_pattern_selection(PatternSelection::Threshold)
+                    .set_threshold(threshold);
+                candidates.push(builder.set_dithering(false).build());
+                for algorithm in [
+                    DitheringAlgorithm::FloydSteinberg,
+                    DitheringAlgorithm::Atkinson,
+                ] {
+                    candidates.push(
+                        builder
+                            .set_dithering(true)
+                            .set_dithering_algorithm(algorithm)
+                            .build(),
+                    );
+                }
+            }
+            candidates.push(
+                builder
+                    .set_dithering(false)
+                    .set_pattern_selection(PatternSelection::ErrorMinimizing)
+                    .build(),
+            );
+        }
+    }
+    candidates
+}
+
+fn compare(source: &GrayImage, art: &GrayImage) -> QualityReport {
+    if art.width() != source.width() || art.height() != source.height() || art.is_empty() {
+        return QualityReport {
+            psnr: 0.0,
+            ssim: 0.0,
+            mean_brightness_error: 0.0,
+        };
+    }
+    let (width, height) = (art.width() as usize, art.height() as usize);
+    let (source, art) = (
+        source
+            .as_raw()
+            .iter()
+            .map(|&v| v as f64)
+            .collect::<Vec<_>>(),
+        art.as_raw().iter().map(|&v| v as f64).collect::<Vec<_>>(),
+    );
+
+    let mse = source
+        .iter()
+        .zip(&art)
+        .map(|(s, a)| (s - a) * (s - a))
+        .sum::<f64>()
+        / source.len() as f64;
+    let psnr = match mse == 0.0 {
+        true => f64::INFINITY,
+        false => 10.0 * (255.0 * 255.0 / mse).log10(),
+    };
+
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+    let mean_brightness_error = (mean(&art) - mean(&source)) / 255.0;
+
+    QualityReport {
+        psnr,
+        ssim: ssim(&source, &art, width, height),
+        mean_brightness_error,
+    }
+}
+
+/// mean SSIM over overlapping windows, a single window if the image is smaller than one
+fn ssim(source: &[f64], art: &[f64], width: usize, height: usize) -> f64 {
+    let window_positions = |size: usize| -> Vec<usize> {
+        match size > SSIM_WINDOW {
+            true => (0..=size - SSIM_WINDOW).step_by(SSIM_STEP).collect(),
+            false => vec![0],
+        }
+    };
+    let (window_width, window_height) = (SSIM_WINDOW.min(width), SSIM_WINDOW.min(height));
+
+    let mut ssim_sum = 0.0;
+    let mut window_count = 0;
+    for wy in window_positions(height) {
+        for wx in window_positions(width) {
+            let indexes = (wy..wy + window_height)
+                .flat_map(|y| (wx..wx + window_width).map(move |x| y * width + x));
+            let n = (window_width * window_height) as f64;
+
+            let (mut sum_s, mut sum_a, mut sum_ss, mut sum_aa, mut sum_sa) =
+                (0.0, 0.0, 0.0, 0.0, 0.0);
+            for i in indexes {
+                let (s, a) = (source[i], art[i]);
+                sum_s += s;
+                sum_a += a;
+                sum_ss += s * s;
+                sum_aa += a * a;
+                sum_sa += s * a;
+            }
+            let (mean_s, mean_a) = (sum_s / n, sum_a / n);
+            let (var_s, var_a, covariance) = (
+                sum_ss / n - mean_s * mean_s,
+                sum_aa / n - mean_a * mean_a,
+                sum_sa / n - mean_s * mean_a,
+            );
+
+            ssim_sum += ((2.0 * mean_s * mean_a + SSIM_C1) * (2.0 * covariance + SSIM_C2))
+                / ((mean_s * mean_s + mean_a * mean_a + SSIM_C1) * (var_s + var_a + SSIM_C2));
+            window_count += 1;
+        }
+    }
+
+    ssim_sum / window_count as f64
+}
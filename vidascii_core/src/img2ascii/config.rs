@@ -0,0 +1,78 @@
+use crate::img2braille::config::Preset;
+
+/// How a cell of the original image is compared to a glyph bitmap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchMetric {
+    /// sum of squared differences, the glyph with the lowest one wins
+    Ssd,
+    /// structural similarity, the glyph with the highest one wins
+    Ssim,
+}
+
+pub struct Img2AsciiOptionsBuilder {
+    /// candidate characters
+    charset: String,
+    metric: MatchMetric,
+    diffuse_error: bool,
+}
+
+impl Img2AsciiOptionsBuilder {
+    pub fn set_charset(mut self, charset: &str) -> Self {
+        self.charset = charset.to_string();
+        self
+    }
+    pub fn set_metric(mut self, metric: MatchMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+    pub fn set_diffuse_error(mut self, diffuse_error: bool) -> Self {
+        self.diffuse_error = diffuse_error;
+        self
+    }
+    /// overrides `diffuse_error`
+    pub fn set_preset(self, preset: Preset) -> Self {
+        self.set_diffuse_error(preset == Preset::Quality)
+    }
+    pub fn build(self) -> Img2AsciiOptions {
+        let mut charset = self.charset.chars().collect::<Vec<_>>();
+        charset.sort_unstable();
+        charset.dedup();
+
+        Img2AsciiOptions {
+            charset,
+            metric: self.metric,
+            diffuse_error: self.diffuse_error,
+        }
+    }
+}
+
+impl Default for Img2AsciiOptionsBuilder {
+    fn default() -> Self {
+        Self {
+            charset: (' '..='~').collect(),
+            metric: MatchMetric::Ssd,
+            diffuse_error: false,
+        }
+    }
+}
+
+/// use: `Img2AsciiOptions::builder`
+pub struct Img2AsciiOptions {
+    /// candidate characters, sorted and without duplicates
+    pub charset: Vec<char>,
+    pub metric: MatchMetric,
+    /// diffuse what the chosen glyph could not represent to the neighbor characters
+    pub diffuse_error: bool,
+}
+
+impl Img2AsciiOptions {
+    pub fn builder() -> Img2AsciiOptionsBuilder {
+        Img2AsciiOptionsBuilder::default()
+    }
+}
+
+impl Default for Img2AsciiOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
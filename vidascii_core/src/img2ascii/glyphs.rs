@@ -0,0 +1,85 @@
+use rusttype::{point, Font, Scale};
+
+/// size (in pixels) at which each glyph is rasterized to be compared with the original image
+pub const GLYPH_WIDTH: u32 = 8;
+pub const GLYPH_HEIGHT: u32 = 16;
+
+pub fn load_ascii_font() -> Option<Font<'static>> {
+    let font_data: &[u8] = include_bytes!("../../fonts/DejaVuSansMono.ttf");
+    Font::try_from_bytes(font_data)
+}
+
+pub struct Glyph {
+    pub character: char,
+    /// row-major coverage of each pixels, between 0.0 (empty) and 1.0 (full)
+    pub coverage: Vec<f32>,
+    pub mean: f32,
+    pub variance: f32,
+}
+
+pub struct GlyphSet {
+    pub glyphs: Vec<Glyph>,
+}
+
+impl GlyphSet {
+    /// rasterize every character of `charset` available in `font` into a `GLYPH_WIDTH`x`GLYPH_HEIGHT` bitmap
+    pub fn rasterize(font: &Font, charset: &[char]) -> Result<Self, &'static str> {
+        let v_metrics = font.v_metrics(Scale::uniform(1.0));
+        let scale_y = GLYPH_HEIGHT as f32 / (v_metrics.ascent - v_metrics.descent);
+
+        let glyphs = charset
+            .iter()
+            .filter(|c| !c.is_control())
+            .filter_map(|&character| {
+                let glyph = font.glyph(character);
+                if glyph.id().0 == 0 {
+                    return None; // not in font
+                }
+
+                let advance_width = glyph
+                    .clone()
+                    .scaled(Scale::uniform(scale_y))
+                    .h_metrics()
+                    .advance_width;
+                let scale = Scale {
+                    x: scale_y * GLYPH_WIDTH as f32 / advance_width.max(1.0),
+                    y: scale_y,
+                };
+
+                let mut coverage = vec![0.0_f32; (GLYPH_WIDTH * GLYPH_HEIGHT) as usize];
+                let glyph = glyph
+                    .scaled(scale)
+                    .positioned(point(0.0, v_metrics.ascent * scale_y));
+                if let Some(bounding_box) = glyph.pixel_bounding_box() {
+                    glyph.draw(|x, y, v| {
+                        let (x, y) = (x as i32 + bounding_box.min.x, y as i32 + bounding_box.min.y);
+                        if (0..GLYPH_WIDTH as i32).contains(&x)
+                            && (0..GLYPH_HEIGHT as i32).contains(&y)
+                        {
+                            coverage[(y as u32 * GLYPH_WIDTH + x as u32) as usize] = v;
+                        }
+                    });
+                }
+
+                let (mean, variance) = mean_and_variance(&coverage);
+                Some(Glyph {
+                    character,
+                    coverage,
+                    mean,
+                    variance,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if glyphs.is_empty() {
+            return Err("None of the characters are available in the font");
+        }
+        Ok(Self { glyphs })
+    }
+}
+
+pub fn mean_and_variance(values: &[f32]) -> (f32, f32) {
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32;
+    (mean, variance)
+}
@@ -0,0 +1,168 @@
+pub mod config;
+pub(crate) mod glyphs;
+
+use image::{
+    imageops::{self, FilterType},
+    GenericImageView, GrayImage, Luma,
+};
+
+use crate::{
+    braille2img::{
+        config::Braille2ImgOptions, font::char_metrics, sizing::with_source_size, text_to_image,
+    },
+    img2braille::error_minimizing::DIFFUSION,
+    utils::{decode_image, to_grayscale},
+    CoreError,
+};
+
+use self::{
+    config::{Img2AsciiOptions, MatchMetric},
+    glyphs::{load_ascii_font, mean_and_variance, Glyph, GlyphSet, GLYPH_HEIGHT, GLYPH_WIDTH},
+};
+
+const SSIM_C1: f32 = 0.01 * 0.01;
+const SSIM_C2: f32 = 0.03 * 0.03;
+
+/// Shape-matching ASCII art: each character is the glyph whose bitmap looks the most like the area of the image it covers.
+///
+/// `ratio` has the same meaning as in `image_to_braille`: one character covers `2*ratio` x `4*ratio` pixels of the original image.
+/// Only the colors and font size of `render_options` are used, the characters size comes from the ascii font
+pub fn image_to_ascii(
+    image_bytes: &[u8],
+    ratio: f32,
+    options: Option<Img2AsciiOptions>,
+    render_options: Option<Braille2ImgOptions>,
+    set_progress: Option<impl Fn(&'static str, u8)>,
+) -> Result<Vec<u8>, CoreError> {
+    let report = |msg: &'static str, new_position: u8| {
+        if let Some(ref set_report) = set_progress {
+            set_report(msg, new_position);
+        }
+    };
+    let options = options.unwrap_or_default();
+
+    // decode img
+    report("decoding image...", 0);
+    let img = decode_image(image_bytes)?;
+
+    report("rasterizing glyphs...", 25);
+    let font = load_ascii_font().ok_or(CoreError::FailedToConvertToAscii)?;
+    let glyph_set = GlyphSet::rasterize(&font, &options.charset)
+        .map_err(|_| CoreError::FailedToConvertToAscii)?;
+
+    report("Matching image to glyphs...", 50);
+    let ascii_text = match_glyphs(&to_grayscale(&img), ratio, &glyph_set, &options);
+
+    report("Converting ascii to image...", 75);
+    let render_options = with_source_size(render_options, img.dimensions()).unwrap_or_default();
+    let (char_width, char_height) = char_metrics(&font, 'M', render_options.font_size);
+    let render_options = Braille2ImgOptions {
+        char_width,
+        char_height,
+        ..render_options
+    };
+    let ascii_img_datas = text_to_image(&ascii_text, &render_options, &font, None)
+        .map_err(|_| CoreError::FailedToConvertToImage)?;
+
+    report("Image converted", 100);
+
+    Ok(ascii_img_datas)
+}
+
+pub(crate) fn match_glyphs(
+    gray: &GrayImage,
+    ratio: f32,
+    glyph_set: &GlyphSet,
+    options: &Img2AsciiOptions,
+) -> String {
+    let (width_chars_count, height_chars_count) = (
+        ((gray.width() as f32 / (ratio * 2.0)).ceil() as u32).max(1),
+        ((gray.height() as f32 / (ratio * 4.0)).ceil() as u32).max(1),
+    );
+
+    // one glyph-sized patch per character
+    let resized = imageops::resize(
+        gray,
+        width_chars_count * GLYPH_WIDTH,
+        height_chars_count * GLYPH_HEIGHT,
+        FilterType::Triangle,
+    );
+
+    let (width, height) = (width_chars_count as usize, height_chars_count as usize);
+    let glyph_size = (GLYPH_WIDTH * GLYPH_HEIGHT) as usize;
+    // accumulated error of each pixels of each characters
+    let mut errors = vec![vec![0.0_f32; glyph_size]; width * height];
+
+    let mut patch = vec![0.0_f32; glyph_size];
+    let mut lines = Vec::with_capacity(height);
+    for char_y in 0..height {
+        let mut line = String::with_capacity(width);
+        for char_x in 0..width {
+            let cell_errors = &errors[char_y * width + char_x];
+            for (i, value) in patch.iter_mut().enumerate() {
+                let (inner_x, inner_y) = (i as u32 % GLYPH_WIDTH, i as u32 / GLYPH_WIDTH);
+                let Luma([brightness]) = resized.get_pixel(
+                    char_x as u32 * GLYPH_WIDTH + inner_x,
+                    char_y as u32 * GLYPH_HEIGHT + inner_y,
+                );
+                *value = (*brightness as f32 / 255.0 + cell_errors[i]).clamp(-0.5, 1.5);
+            }
+
+            let glyph = best_glyph(&patch, glyph_set, options.metric);
+            line.push(glyph.character);
+
+            if options.diffuse_error {
+                for (dx, dy, coef) in DIFFUSION {
+                    let (nx, ny) = (char_x as isize + dx, char_y + dy);
+                    if nx < 0 || nx as usize >= width || ny >= height {
+                        continue;
+                    }
+                    let neighbor_errors = &mut errors[ny * width + nx as usize];
+                    for (i, error) in neighbor_errors.iter_mut().enumerate() {
+                        *error += (patch[i] - glyph.coverage[i]) * coef;
+                    }
+                }
+            }
+        }
+        lines.push(line);
+    }
+
+    lines.join("\n")
+}
+
+fn best_glyph<'a>(patch: &[f32], glyph_set: &'a GlyphSet, metric: MatchMetric) -> &'a Glyph {
+    let score = |glyph: &Glyph| match metric {
+        MatchMetric::Ssd => ssd(patch, &glyph.coverage),
+        MatchMetric::Ssim => -ssim(patch, glyph),
+    };
+
+    glyph_set
+        .glyphs
+        .iter()
+        .map(|glyph| (score(glyph), glyph))
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, glyph)| glyph)
+        .expect("glyph set is never empty")
+}
+
+fn ssd(patch: &[f32], coverage: &[f32]) -> f32 {
+    patch
+        .iter()
+        .zip(coverage)
+        .map(|(p, c)| (p - c).powi(2))
+        .sum()
+}
+
+fn ssim(patch: &[f32], glyph: &Glyph) -> f32 {
+    let (patch_mean, patch_variance) = mean_and_variance(patch);
+    let covariance = patch
+        .iter()
+        .zip(&glyph.coverage)
+        .map(|(p, c)| (p - patch_mean) * (c - glyph.mean))
+        .sum::<f32>()
+        / patch.len() as f32;
+
+    ((2.0 * patch_mean * glyph.mean + SSIM_C1) * (2.0 * covariance + SSIM_C2))
+        / ((patch_mean.powi(2) + glyph.mean.powi(2) + SSIM_C1)
+            * (patch_variance + glyph.variance + SSIM_C2))
+}
@@ -0,0 +1,138 @@
+use super::GrayScaleMode;
+
+/// Error diffusion used by the dithering pre-processing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DitheringAlgorithm {
+    /// @see: https://en.wikipedia.org/wiki/Floyd%E2%80%93Steinberg_dithering
+    FloydSteinberg,
+    /// diffuses only 3/4 of the error, keeps more contrast
+    Atkinson,
+}
+
+/// How the dots pattern of each braille character is chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternSelection {
+    /// each dot is raised independently when its brightness is above 128, fast
+    Threshold,
+    /// all the 256 patterns are compared to the cell brightness and the closest one is kept,
+    /// the remaining error is diffused to the neighbor cells. Slower but sharper
+    ErrorMinimizing,
+}
+
+/// Ready-made sets of options, see also `eval::find_best_options` to pick them automatically for an image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// threshold + Floyd-Steinberg dithering (the historical behavior)
+    Fast,
+    /// error-minimizing pattern selection
+    Quality,
+}
+
+#[derive(Clone, Copy)]
+pub struct Img2BrailleOptionsBuilder {
+    ratio: f32,
+    dithering: bool,
+    dithering_algorithm: DitheringAlgorithm,
+    pattern_selection: PatternSelection,
+    threshold: u8,
+    gamma: f32,
+    grayscale: Option<GrayScaleMode>,
+}
+
+impl Img2BrailleOptionsBuilder {
+    pub fn set_ratio(mut self, ratio: f32) -> Self {
+        self.ratio = ratio;
+        self
+    }
+    pub fn set_dithering(mut self, dithering: bool) -> Self {
+        self.dithering = dithering;
+        self
+    }
+    pub fn set_dithering_algorithm(mut self, algorithm: DitheringAlgorithm) -> Self {
+        self.dithering_algorithm = algorithm;
+        self
+    }
+    pub fn set_pattern_selection(mut self, pattern_selection: PatternSelection) -> Self {
+        self.pattern_selection = pattern_selection;
+        self
+    }
+    pub fn set_threshold(mut self, threshold: u8) -> Self {
+        self.threshold = threshold;
+        self
+    }
+    pub fn set_gamma(mut self, gamma: f32) -> Self {
+        self.gamma = gamma;
+        self
+    }
+    pub fn set_grayscale(mut self, grayscale: GrayScaleMode) -> Self {
+        self.grayscale = Some(grayscale);
+        self
+    }
+    /// overrides `dithering`, `dithering_algorithm` and `pattern_selection`
+    pub fn set_preset(self, preset: Preset) -> Self {
+        match preset {
+            Preset::Fast => self
+                .set_dithering(true)
+                .set_dithering_algorithm(DitheringAlgorithm::FloydSteinberg)
+                .set_pattern_selection(PatternSelection::Threshold),
+            Preset::Quality => self
+                .set_dithering(false)
+                .set_pattern_selection(PatternSelection::ErrorMinimizing),
+        }
+    }
+    pub fn build(self) -> Img2BrailleOptions {
+        Img2BrailleOptions {
+            ratio: self.ratio,
+            dithering: self.dithering,
+            dithering_algorithm: self.dithering_algorithm,
+            pattern_selection: self.pattern_selection,
+            threshold: self.threshold,
+            gamma: self.gamma,
+            grayscale: self.grayscale,
+        }
+    }
+}
+
+impl Default for Img2BrailleOptionsBuilder {
+    fn default() -> Self {
+        Self {
+            ratio: 1.0,
+            dithering: true,
+            dithering_algorithm: DitheringAlgorithm::FloydSteinberg,
+            pattern_selection: PatternSelection::Threshold,
+            threshold: 128,
+            gamma: 1.0,
+            grayscale: None,
+        }
+    }
+}
+
+/// use: `Img2BrailleOptions::builder`
+#[derive(Debug, Clone, Copy)]
+pub struct Img2BrailleOptions {
+    /// `ratio` is an integer greater than 1 that can be describred by this sentences: "1 pixel on the braille image equals <ratio> pixels on the original image"
+    pub ratio: f32,
+    /// apply dithering before converting
+    pub dithering: bool,
+    pub dithering_algorithm: DitheringAlgorithm,
+    pub pattern_selection: PatternSelection,
+    /// brightness from which a dot is raised (`PatternSelection::Threshold` and dithering)
+    pub threshold: u8,
+    /// applied to the image before anything else: brightness = (brightness / 255)^(1 / gamma) * 255,
+    /// above 1.0 brightens the midtones, below darkens them
+    pub gamma: f32,
+    /// `None`: luminance when a pixel spans several dots (ratio below 1), average otherwise and for dithering
+    pub grayscale: Option<GrayScaleMode>,
+}
+
+impl Img2BrailleOptions {
+    pub fn builder() -> Img2BrailleOptionsBuilder {
+        Img2BrailleOptionsBuilder::default()
+    }
+}
+
+impl Default for Img2BrailleOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
@@ -1,151 +1,94 @@
 use std::error::Error;
 
-use image::DynamicImage;
-
-pub struct FloydSteinbergDithering();
-impl FloydSteinbergDithering {
-    pub fn apply_to(img: &mut DynamicImage) -> Result<(), Box<dyn Error>> {
-        if img.as_mut_rgba8().is_some() {
-            return floyd_steinberg_dithering_rgba::apply_to(img);
-        } else if img.as_mut_rgb8().is_some() {
-            return floyd_steinberg_dithering_rgb::apply_to(img);
-        }
-        Err("Failed to get pixels".into())
-    }
+use image::{DynamicImage, ImageBuffer, Pixel};
+
+use crate::img2braille::{compute_brightness, config::DitheringAlgorithm, GrayScaleMode};
+
+/// (dx, dy, coef) of the error diffused to each neighbor pixel
+const FLOYD_STEINBERG_KERNEL: [(i64, i64, f32); 4] = [
+    (1, 0, 7.0 / 16.0),
+    (-1, 1, 3.0 / 16.0),
+    (0, 1, 5.0 / 16.0),
+    (1, 1, 1.0 / 16.0),
+];
+/// only 3/4 of the error is diffused, which keeps more contrast
+const ATKINSON_KERNEL: [(i64, i64, f32); 6] = [
+    (1, 0, 1.0 / 8.0),
+    (2, 0, 1.0 / 8.0),
+    (-1, 1, 1.0 / 8.0),
+    (0, 1, 1.0 / 8.0),
+    (1, 1, 1.0 / 8.0),
+    (0, 2, 1.0 / 8.0),
+];
+
+/// Turns each pixel black or white depending on its brightness, diffusing the error to its neighbors
+pub struct Dithering {
+    pub algorithm: DitheringAlgorithm,
+    /// pixels at least this bright become white
+    pub threshold: u8,
+    pub grayscale: GrayScaleMode,
 }
 
-mod floyd_steinberg_dithering_rgba {
-    use std::error::Error;
-
-    use image::{DynamicImage, Rgba};
+impl Dithering {
+    pub fn apply_to(&self, img: &mut DynamicImage) -> Result<(), Box<dyn Error>> {
+        let kernel: &[(i64, i64, f32)] = match self.algorithm {
+            DitheringAlgorithm::FloydSteinberg => &FLOYD_STEINBERG_KERNEL,
+            DitheringAlgorithm::Atkinson => &ATKINSON_KERNEL,
+        };
 
-    use crate::img2braille::{compute_brightness, GrayScaleMode};
-
-    pub fn apply_to(img: &mut DynamicImage) -> Result<(), Box<dyn Error>> {
-        let (width, height) = (img.width(), img.height());
-        let pixels = img.as_mut_rgba8().unwrap();
-
-        for y in 0..height {
-            for x in 0..width {
-                let opixel = pixels.get_pixel_mut(x, y);
-                let npixel = find_closest_color(opixel);
-
-                let quant_error = [
-                    opixel.0[0] as i16 - npixel[0] as i16,
-                    opixel.0[1] as i16 - npixel[1] as i16,
-                    opixel.0[2] as i16 - npixel[2] as i16,
-                ];
-                *opixel = Rgba(npixel);
-
-                propagate_error(
-                    pixels.get_pixel_mut_checked(x + 1, y),
-                    quant_error,
-                    7.0 / 16.0,
-                );
-                if x > 0 {
-                    propagate_error(
-                        pixels.get_pixel_mut_checked(x - 1, y + 1),
-                        quant_error,
-                        3.0 / 16.0,
-                    );
-                }
-                propagate_error(
-                    pixels.get_pixel_mut_checked(x, y + 1),
-                    quant_error,
-                    5.0 / 16.0,
-                );
-                propagate_error(
-                    pixels.get_pixel_mut_checked(x + 1, y + 1),
-                    quant_error,
-                    1.0 / 16.0,
-                );
-            }
+        if img.as_rgba8().is_none() && img.as_rgb8().is_none() {
+            *img = DynamicImage::ImageRgba8(img.to_rgba8());
         }
-
-        Ok(())
-    }
-
-    fn propagate_error(pixels: Option<&mut Rgba<u8>>, error: [i16; 3], coef: f32) {
-        if let Some(Rgba([r, g, b, _])) = pixels {
-            *r = (*r as f32 + error[0] as f32 * coef).max(0.0).min(255.0) as u8;
-            *g = (*g as f32 + error[1] as f32 * coef).max(0.0).min(255.0) as u8;
-            *b = (*b as f32 + error[2] as f32 * coef).max(0.0).min(255.0) as u8;
-        }
-    }
-
-    fn find_closest_color(Rgba([r, g, b, _]): &Rgba<u8>) -> [u8; 4] {
-        if compute_brightness([*r, *g, *b], GrayScaleMode::Average) >= 128 {
-            [255, 255, 255, 255]
-        } else {
-            [0, 0, 0, 255]
+        if let Some(pixels) = img.as_mut_rgba8() {
+            return self.diffuse(pixels, kernel);
+        } else if let Some(pixels) = img.as_mut_rgb8() {
+            return self.diffuse(pixels, kernel);
         }
+        Err("Failed to get pixels".into())
     }
-}
-
-mod floyd_steinberg_dithering_rgb {
-    use std::error::Error;
-
-    use image::{DynamicImage, Rgb};
-
-    use crate::img2braille::{compute_brightness, GrayScaleMode};
 
-    pub fn apply_to(img: &mut DynamicImage) -> Result<(), Box<dyn Error>> {
-        let (width, height) = (img.width(), img.height());
-        let pixels = img.as_mut_rgb8().unwrap();
+    fn diffuse<P: Pixel<Subpixel = u8>>(
+        &self,
+        pixels: &mut ImageBuffer<P, Vec<u8>>,
+        kernel: &[(i64, i64, f32)],
+    ) -> Result<(), Box<dyn Error>> {
+        let (width, height) = pixels.dimensions();
 
         for y in 0..height {
             for x in 0..width {
                 let opixel = pixels.get_pixel_mut(x, y);
-                let npixel = find_closest_color(opixel);
+                let channels = opixel.channels_mut();
+                let [r, g, b] = [channels[0], channels[1], channels[2]];
+                let npixel = self.find_closest_color([r, g, b]);
 
                 let quant_error = [
-                    opixel.0[0] as i16 - npixel[0] as i16,
-                    opixel.0[1] as i16 - npixel[1] as i16,
-                    opixel.0[2] as i16 - npixel[2] as i16,
+                    r as i16 - npixel as i16,
+                    g as i16 - npixel as i16,
+                    b as i16 - npixel as i16,
                 ];
-                *opixel = Rgb(npixel);
-
-                propagate_error(
-                    pixels.get_pixel_mut_checked(x + 1, y),
-                    quant_error,
-                    7.0 / 16.0,
-                );
-                if x > 0 {
-                    propagate_error(
-                        pixels.get_pixel_mut_checked(x - 1, y + 1),
-                        quant_error,
-                        3.0 / 16.0,
-                    );
+                channels[..3].fill(npixel);
+
+                for &(dx, dy, coef) in kernel {
+                    let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                    if nx < 0 || nx >= width as i64 || ny >= height as i64 {
+                        continue;
+                    }
+                    let neighbor = pixels.get_pixel_mut(nx as u32, ny as u32).channels_mut();
+                    for (channel, error) in neighbor.iter_mut().zip(quant_error) {
+                        *channel = (*channel as f32 + error as f32 * coef).clamp(0.0, 255.0) as u8;
+                    }
                 }
-                propagate_error(
-                    pixels.get_pixel_mut_checked(x, y + 1),
-                    quant_error,
-                    5.0 / 16.0,
-                );
-                propagate_error(
-                    pixels.get_pixel_mut_checked(x + 1, y + 1),
-                    quant_error,
-                    1.0 / 16.0,
-                );
             }
         }
 
         Ok(())
     }
 
-    fn propagate_error(pixels: Option<&mut Rgb<u8>>, error: [i16; 3], coef: f32) {
-        if let Some(Rgb([r, g, b])) = pixels {
-            *r = (*r as f32 + error[0] as f32 * coef).clamp(0.0, 255.0) as u8;
-            *g = (*g as f32 + error[1] as f32 * coef).clamp(0.0, 255.0) as u8;
-            *b = (*b as f32 + error[2] as f32 * coef).clamp(0.0, 255.0) as u8;
-        }
-    }
-
-    fn find_closest_color(Rgb([r, g, b]): &Rgb<u8>) -> [u8; 3] {
-        if compute_brightness([*r, *g, *b], GrayScaleMode::Average) >= 128 {
-            [255, 255, 255]
+    fn find_closest_color(&self, rgb: [u8; 3]) -> u8 {
+        if compute_brightness(rgb, self.grayscale) >= self.threshold as u16 {
+            255
         } else {
-            [0, 0, 0]
+            0
         }
     }
 }
@@ -0,0 +1,97 @@
+use crate::braille::{BrailleCell, CELL_HEIGHT, CELL_WIDTH};
+
+/// Floyd-Steinberg weights, applied at the cell level: (dx, dy, coef)
+pub(crate) const DIFFUSION: [(isize, usize, f32); 4] = [
+    (1, 0, 7.0 / 16.0),
+    (-1, 1, 3.0 / 16.0),
+    (0, 1, 5.0 / 16.0),
+    (1, 1, 1.0 / 16.0),
+];
+
+/// For each cell, evaluates the 256 braille patterns against the cell brightness and keeps the one with the lowest error,
+/// the residual of each dot is then diffused to the same dot of the neighbor cells.
+///
+/// `avg_pixels` brightness are between 0 and 255, 256 meaning "no pixel" (the dot is never raised)
+pub fn select_patterns(avg_pixels: &[Vec<[[u16; 4]; 2]>]) -> Vec<Vec<BrailleCell>> {
+    let (height, width) = (
+        avg_pixels.len(),
+        avg_pixels.first().map_or(0, |row| row.len()),
+    );
+    let mut errors = vec![vec![[[0.0_f32; 4]; 2]; width]; height];
+
+    let mut braille_pixels = Vec::with_capacity(height);
+    for char_y in 0..height {
+        let mut line = Vec::with_capacity(width);
+        for char_x in 0..width {
+            let cell = &avg_pixels[char_y][char_x];
+
+            let mut targets = [[None; CELL_HEIGHT]; CELL_WIDTH];
+            let mut unknown_dots = BrailleCell::EMPTY;
+            for (x, y) in positions() {
+                if cell[x][y] != 256 {
+                    let target = cell[x][y] as f32 / 255.0 + errors[char_y][char_x][x][y];
+                    targets[x][y] = Some(target.clamp(-0.5, 1.5));
+                } else {
+                    unknown_dots.set(x, y);
+                }
+            }
+
+            let best_pattern = (0..=u8::MAX)
+                .map(BrailleCell)
+                .filter(|&pattern| (pattern & unknown_dots) == BrailleCell::EMPTY)
+                .map(|pattern| (pattern_error(&targets, pattern), pattern))
+                .min_by(|(a, _), (b, _)| a.total_cmp(b))
+                .map(|(_, pattern)| pattern)
+                .unwrap_or_default();
+
+            // diffuse what could not be represented
+            for (x, y) in positions() {
+                let Some(target) = targets[x][y] else {
+                    continue;
+                };
+                let residual = target - dot_value(best_pattern, x, y);
+                for (dx, dy, coef) in DIFFUSION {
+                    let (nx, ny) = (char_x as isize + dx, char_y + dy);
+                    if nx < 0 || nx as usize >= width || ny >= height {
+                        continue;
+                    }
+                    errors[ny][nx as usize][x][y] += residual * coef;
+                }
+            }
+
+            line.push(best_pattern);
+        }
+        braille_pixels.push(line);
+    }
+
+    braille_pixels
+}
+
+fn positions() -> impl Iterator<Item = (usize, usize)> {
+    (0..CELL_WIDTH).flat_map(|x| (0..CELL_HEIGHT).map(move |y| (x, y)))
+}
+
+fn dot_value(pattern: BrailleCell, x: usize, y: usize) -> f32 {
+    match pattern.get(x, y) {
+        true => 1.0,
+        false => 0.0,
+    }
+}
+
+/// per-dot squared error plus the error on the overall cell tone, since the eye averages neighbor dots
+fn pattern_error(targets: &[[Option<f32>; CELL_HEIGHT]; CELL_WIDTH], pattern: BrailleCell) -> f32 {
+    let (mut detail_error, mut target_tone, mut pattern_tone) = (0.0, 0.0, 0.0);
+    for (x, y) in positions() {
+        let Some(target) = targets[x][y] else {
+            continue;
+        };
+        let value = dot_value(pattern, x, y);
+        detail_error += (target - value) * (target - value);
+        target_tone += target;
+        pattern_tone += value;
+    }
+
+    detail_error
+        + (target_tone - pattern_tone) * (target_tone - pattern_tone)
+            / (CELL_WIDTH * CELL_HEIGHT) as f32
+}
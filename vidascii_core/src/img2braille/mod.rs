@@ -1,30 +1,165 @@
+pub mod config;
 mod dithering;
+pub(crate) mod error_minimizing;
 
-use image::{io::Reader as ImageReader, GenericImageView, Rgba};
-use std::io::Cursor;
+use image::{imageops::FilterType, DynamicImage, GenericImageView, RgbImage, Rgba};
 
-use crate::{braille2img::braille_to_image, CoreError};
+use crate::{
+    braille::{BrailleCell, BrailleImage},
+    braille2img::{
+        braille_art_comparison_to_image, braille_art_comparison_to_rgb_image, braille_art_to_image,
+        braille_art_to_rgb_image,
+        config::Braille2ImgOptions,
+        html::braille_art_to_html,
+        sizing::with_source_size,
+        svg::{braille_art_to_svg, SvgFont},
+    },
+    utils::decode_image,
+    CoreError,
+};
 
-use self::dithering::FloydSteinbergDithering;
+use self::{
+    config::{Img2BrailleOptions, PatternSelection},
+    dithering::Dithering,
+};
 
-const DOTS_POS: [(usize, usize, u8); 8] = [
-    (0, 0, 0),
-    (0, 1, 1),
-    (0, 2, 2),
-    (0, 3, 6),
-    (1, 0, 3),
-    (1, 1, 4),
-    (1, 2, 5),
-    (1, 3, 7),
-];
-
-/// `ratio` is an integer greater than 1 that can be describred by this sentences: "1 pixel on the braille image equals <ratio> pixels on the original image"
+/// see `Img2BrailleOptions` for the conversion settings and `Braille2ImgOptions` for the rendering ones
 pub fn image_to_braille(
     image_bytes: &[u8],
-    ratio: f32,
-    dithering: bool,
+    options: Option<Img2BrailleOptions>,
+    render_options: Option<Braille2ImgOptions>,
     set_progress: Option<impl Fn(&'static str, u8)>,
 ) -> Result<Vec<u8>, CoreError> {
+    if let Some(ref set_report) = set_progress {
+        set_report("decoding image...", 0);
+    }
+    let img = decode_image(image_bytes)?;
+    let render_options = with_source_size(render_options, img.dimensions());
+    // the conversion edits the image
+    let source = render_options
+        .as_ref()
+        .and_then(|render_options| render_options.comparison)
+        .map(|_| img.clone());
+    let braille_image = dynamic_image_to_braille_art(img, options, set_progress.as_ref())?;
+
+    if let Some(ref set_report) = set_progress {
+        set_report("Converting braille to image...", 75);
+    }
+    let braille_img_datas = match source {
+        Some(source) => braille_art_comparison_to_image(&source, &braille_image, render_options),
+        None => braille_art_to_image(&braille_image, render_options),
+    }
+    .map_err(|_| CoreError::FailedToConvertToImage)?;
+
+    if let Some(ref set_report) = set_progress {
+        set_report("Image converted", 100);
+    }
+
+    Ok(braille_img_datas)
+}
+
+/// same as `image_to_braille`, for an already decoded image and without encoding
+pub fn dynamic_image_to_braille_rgb_image(
+    img: DynamicImage,
+    options: Option<Img2BrailleOptions>,
+    render_options: Option<Braille2ImgOptions>,
+) -> Result<RgbImage, CoreError> {
+    let render_options = with_source_size(render_options, img.dimensions());
+    // the conversion edits the image
+    let source = render_options
+        .as_ref()
+        .and_then(|render_options| render_options.comparison)
+        .map(|_| img.clone());
+    let braille_image = dynamic_image_to_braille_art(img, options, None::<fn(&'static str, u8)>)?;
+
+    match source {
+        Some(source) => {
+            braille_art_comparison_to_rgb_image(&source, &braille_image, render_options)
+        }
+        None => braille_art_to_rgb_image(&braille_image, render_options),
+    }
+    .map_err(|_| CoreError::FailedToConvertToImage)
+}
+
+/// same as `image_to_braille`, rendered as an SVG document
+pub fn image_to_braille_svg(
+    image_bytes: &[u8],
+    options: Option<Img2BrailleOptions>,
+    render_options: Option<Braille2ImgOptions>,
+    font: SvgFont,
+    set_progress: Option<impl Fn(&'static str, u8)>,
+) -> Result<String, CoreError> {
+    if let Some(ref set_report) = set_progress {
+        set_report("decoding image...", 0);
+    }
+    let img = decode_image(image_bytes)?;
+    let render_options = with_source_size(render_options, img.dimensions());
+    let braille_image = dynamic_image_to_braille_art(img, options, set_progress.as_ref())?;
+
+    if let Some(ref set_report) = set_progress {
+        set_report("Converting braille to svg...", 75);
+    }
+    let svg = braille_art_to_svg(&braille_image, render_options, font);
+
+    if let Some(ref set_report) = set_progress {
+        set_report("Image converted", 100);
+    }
+
+    Ok(svg)
+}
+
+/// same as `image_to_braille`, rendered as a standalone HTML page.
+/// With `colored`, each character takes the average color of the pixels it covers
+pub fn image_to_braille_html(
+    image_bytes: &[u8],
+    options: Option<Img2BrailleOptions>,
+    render_options: Option<Braille2ImgOptions>,
+    colored: bool,
+    set_progress: Option<impl Fn(&'static str, u8)>,
+) -> Result<String, CoreError> {
+    if let Some(ref set_report) = set_progress {
+        set_report("decoding image...", 0);
+    }
+    let img = decode_image(image_bytes)?;
+    let colors = colored.then(|| img.clone());
+    let braille_image = dynamic_image_to_braille_art(img, options, set_progress.as_ref())?;
+    let colors = colors.map(|img| cell_colors(&img, &braille_image));
+
+    if let Some(ref set_report) = set_progress {
+        set_report("Converting braille to html...", 75);
+    }
+    let html = braille_art_to_html(&braille_image, colors.as_deref(), render_options);
+
+    if let Some(ref set_report) = set_progress {
+        set_report("Image converted", 100);
+    }
+
+    Ok(html)
+}
+
+/// average color of the pixels covered by each character of `art`, `img` being the image it was converted from.
+/// Indexed by `[line][column]`
+pub fn cell_colors(img: &DynamicImage, art: &BrailleImage) -> Vec<Vec<[u8; 3]>> {
+    if art.width() == 0 || art.height() == 0 {
+        return vec![];
+    }
+    img.resize_exact(
+        art.width() as u32,
+        art.height() as u32,
+        FilterType::Triangle,
+    )
+    .to_rgb8()
+    .rows()
+    .map(|row| row.map(|pixel| pixel.0).collect())
+    .collect()
+}
+
+/// same as `image_to_braille`, but returns the braille grid instead of rendering it
+pub fn image_to_braille_art(
+    image_bytes: &[u8],
+    options: Option<Img2BrailleOptions>,
+    set_progress: Option<impl Fn(&'static str, u8)>,
+) -> Result<BrailleImage, CoreError> {
     let report = |msg: &'static str, new_position: u8| {
         if let Some(ref set_report) = set_progress {
             set_report(msg, new_position);
@@ -33,15 +168,46 @@ pub fn image_to_braille(
 
     // decode img
     report("decoding image...", 0);
-    let mut img = ImageReader::new(Cursor::new(image_bytes))
-        .with_guessed_format()
-        .map_err(|_| CoreError::WrongExtension)?
-        .decode()
-        .map_err(|_| CoreError::FrameDecodeError)?;
+    let img = decode_image(image_bytes)?;
+
+    dynamic_image_to_braille_art(img, options, set_progress)
+}
+
+/// same as `image_to_braille_art`, for an already decoded image
+pub fn dynamic_image_to_braille_art(
+    mut img: DynamicImage,
+    options: Option<Img2BrailleOptions>,
+    set_progress: Option<impl Fn(&'static str, u8)>,
+) -> Result<BrailleImage, CoreError> {
+    let report = |msg: &'static str, new_position: u8| {
+        if let Some(ref set_report) = set_progress {
+            set_report(msg, new_position);
+        }
+    };
+    let Img2BrailleOptions {
+        ratio,
+        dithering,
+        dithering_algorithm,
+        pattern_selection,
+        threshold,
+        gamma,
+        grayscale,
+    } = options.unwrap_or_default();
+
+    if gamma != 1.0 {
+        apply_gamma(&mut img, gamma);
+    }
 
     if dithering {
         report("applying dithering...", 25);
-        FloydSteinbergDithering::apply_to(&mut img).map_err(|_| CoreError::DitheringFailed)?;
+        let dithering = Dithering {
+            algorithm: dithering_algorithm,
+            threshold,
+            grayscale: grayscale.unwrap_or(GrayScaleMode::Average),
+        };
+        dithering
+            .apply_to(&mut img)
+            .map_err(|_| CoreError::DitheringFailed)?;
     }
 
     // compute new img width/height
@@ -58,12 +224,13 @@ pub fn image_to_braille(
 
     let sub_divised_by = (1.0 / ratio).round() as u32;
     if sub_divised_by > 1 {
+        let grayscale = grayscale.unwrap_or(GrayScaleMode::Luminance);
         for (x, y, Rgba([r, g, b, a])) in img.pixels() {
             if a < 128 {
                 continue;
             }
 
-            let brightness = compute_brightness([r, g, b], GrayScaleMode::Luminance);
+            let brightness = compute_brightness([r, g, b], grayscale);
             let sub_x_pixels = (x * (sub_divised_by / 2))..((sub_divised_by / 2) * (x + 1));
             let sub_y_lines = sub_divised_by as usize; // = sub_x_pixels.len() * 2
             let inner_chars_lines = sub_y_lines as f32 / 4.0;
@@ -89,12 +256,13 @@ pub fn image_to_braille(
         }
     } else {
         let ratio = ratio.round();
+        let grayscale = grayscale.unwrap_or(GrayScaleMode::Average);
         for (x, y, Rgba([r, g, b, a])) in img.pixels() {
             if a < 128 {
                 continue;
             }
 
-            let brightness = compute_brightness([r, g, b], GrayScaleMode::Average);
+            let brightness = compute_brightness([r, g, b], grayscale);
             let (char_x, char_y) = (
                 (x as f32 / (2.0 * ratio)) as usize,
                 (y as f32 / (4.0 * ratio)) as usize,
@@ -114,45 +282,38 @@ pub fn image_to_braille(
     }
 
     // map pixels to braille according to their brightness
-    let braille_pixels = new_img_avg_pixels
-        .iter()
-        .map(|raw| {
-            raw.iter()
-                .map(|p| {
-                    let mut dots = vec![];
-                    for (x, y, dot) in DOTS_POS {
-                        let avg_brightness = p[x][y];
-                        if avg_brightness == 256 {
-                            continue;
+    let braille_pixels = match pattern_selection {
+        PatternSelection::Threshold => new_img_avg_pixels
+            .iter()
+            .map(|raw| {
+                raw.iter()
+                    .map(|p| {
+                        let mut cell = BrailleCell::EMPTY;
+                        for (x, column) in p.iter().enumerate() {
+                            for (y, &avg_brightness) in column.iter().enumerate() {
+                                if avg_brightness != 256 && avg_brightness >= threshold as u16 {
+                                    cell.set(x, y);
+                                }
+                            }
                         }
-                        if avg_brightness >= 128 {
-                            dots.push(dot)
-                        }
-                    }
-
-                    to_braille(&dots)
-                })
-                .collect::<Result<Vec<_>, _>>()
-        })
-        .collect::<Result<Vec<_>, _>>()?;
-
-    let braille_text = braille_pixels_to_string(braille_pixels);
-
-    report("Converting braille to image...", 75);
-    let braille_img_datas =
-        braille_to_image(&braille_text, None).map_err(|_| CoreError::FailedToConvertToImage)?;
-
-    report("Image converted", 100);
+                        cell
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>(),
+        PatternSelection::ErrorMinimizing => error_minimizing::select_patterns(&new_img_avg_pixels),
+    };
 
-    Ok(braille_img_datas)
+    Ok(BrailleImage::from_cells(braille_pixels))
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GrayScaleMode {
     Luminance,
     Average,
 }
 
-fn compute_brightness([r, g, b]: [u8; 3], mode: GrayScaleMode) -> u16 {
+pub(crate) fn compute_brightness([r, g, b]: [u8; 3], mode: GrayScaleMode) -> u16 {
     match mode {
         GrayScaleMode::Luminance => {
             (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32).round() as u16
@@ -161,23 +322,16 @@ fn compute_brightness([r, g, b]: [u8; 3], mode: GrayScaleMode) -> u16 {
     }
 }
 
-fn braille_pixels_to_string(braille_pixels: Vec<Vec<char>>) -> String {
-    let lines = braille_pixels.join(&'\n');
-
-    let mut img_text = String::new();
-    img_text.extend(lines.iter());
-
-    img_text
-}
-
-fn to_braille(dots: &[u8]) -> Result<char, CoreError> {
-    let offset = dots.iter().fold(0_u32, |acc, &dot| {
-        let all_combination = 2_u32.pow(dot as u32);
-        acc + all_combination
+fn apply_gamma(img: &mut DynamicImage, gamma: f32) {
+    let lookup_table: [u8; 256] = std::array::from_fn(|value| {
+        ((value as f32 / 255.0).powf(1.0 / gamma) * 255.0).round() as u8
     });
 
-    if offset > 255 {
-        return Err(CoreError::FailedToConvertToBraille);
+    let mut rgba = img.to_rgba8();
+    for Rgba([r, g, b, _]) in rgba.pixels_mut() {
+        for channel in [r, g, b] {
+            *channel = lookup_table[*channel as usize];
+        }
     }
-    char::from_u32(0x2800 + offset).ok_or(CoreError::FailedToConvertToBraille)
+    *img = DynamicImage::ImageRgba8(rgba);
 }
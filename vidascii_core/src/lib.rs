@@ -1,4 +1,7 @@
-mod braille2img;
+pub mod braille;
+pub mod braille2img;
+pub mod eval;
+pub mod img2ascii;
 pub mod img2braille;
 mod tests;
 mod utils;
@@ -14,7 +17,9 @@ pub enum CoreError {
     FailedToConvert,
     FrameDecodeError,
     FailedToConvertToBraille,
+    FailedToConvertToAscii,
     FailedToConvertToImage,
+    FailedToLoadFont,
     DitheringFailed,
     FailedToOpenAppPath,
     FailedToSave,
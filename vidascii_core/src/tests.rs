@@ -1,6 +1,907 @@
 #[cfg(test)]
 mod core_tests {
-    use std::{fs, thread, time::Instant};
+    use std::{cell::Cell, fs, path::Path, thread, time::Instant};
+
+    use ffmpeg_sidecar::{command::FfmpegCommand, event::OutputVideoFrame};
+    use scopeguard::defer;
+
+    use image::{DynamicImage, GrayImage, Luma, Rgb};
+
+    use crate::{
+        braille::{
+            decode, decode_to_bitmap, BoolOp, BrailleCell, BrailleImage, ResampleFilter,
+            MAX_RESAMPLED_DOTS,
+        },
+        braille2img::{
+            atlas::render_with_atlas,
+            braille_art_comparison_to_image, braille_art_to_image, braille_art_to_rgb_image,
+            braille_art_to_rgba_image, braille_to_image, braille_to_rgb_image,
+            config::{
+                Braille2ImgOptions, Comparison, CrtEffect, DotShape, DotStyle, Gradient,
+                ImageFormat, OutputSize, PngCompression, Renderer, Theme,
+            },
+            draw_text,
+            font::{embedded_font, CustomFont},
+            html::braille_art_to_html,
+            layout::TextLayout,
+            svg::{braille_art_to_svg, SvgFont},
+            text_to_image,
+        },
+        eval::{evaluate, find_best_options},
+        img2ascii::{
+            config::{Img2AsciiOptions, MatchMetric},
+            glyphs::{load_ascii_font, GlyphSet},
+            match_glyphs,
+        },
+        img2braille::{
+            cell_colors,
+            config::{Img2BrailleOptions, Img2BrailleOptionsBuilder},
+            dynamic_image_to_braille_art,
+            error_minimizing::select_patterns,
+            image_to_braille, image_to_braille_art, GrayScaleMode,
+        },
+        utils::base64_encode,
+        vid2braille::{
+            config::{FrameRate, Vid2BrailleOptions},
+            convert_frames, video_to_braille,
+        },
+        CoreError,
+    };
+
+    #[test]
+    fn braille_cell_test() {
+        let cell = BrailleCell::from_dots(&[1, 4, 8]).unwrap();
+        assert_eq!(cell.to_char(), '⢉');
+        assert_eq!(BrailleCell::from_char('⢉'), Some(cell));
+        assert_eq!(BrailleCell::from_char('a'), None);
+        assert_eq!(BrailleCell::from_dots(&[0]), None);
+        assert_eq!(BrailleCell::from_dots(&[9]), None);
+        assert_eq!(cell.dots(), vec![1, 4, 8]);
+
+        assert!(cell.get(0, 0) && cell.get(1, 0) && cell.get(1, 3));
+        assert!(!cell.get(0, 3));
+        let mut edited = cell;
+        edited.set(0, 3);
+        edited.clear(0, 0);
+        assert_eq!(edited.dots(), vec![4, 7, 8]);
+
+        assert_eq!(cell.invert().count(), 5);
+        assert_eq!(cell.union(cell.invert()), BrailleCell::FULL);
+        assert_eq!(cell.intersect(edited).dots(), vec![4, 8]);
+
+        for dot in 1..=8 {
+            let (x, y) = BrailleCell::dot_position(dot).unwrap();
+            assert_eq!(BrailleCell::dot_number(x, y), Some(dot));
+        }
+        assert_eq!(BrailleCell::dot_position(9), None);
+
+        // out of the cell
+        assert_eq!(BrailleCell::dot_number(2, 0), None);
+        assert_eq!(BrailleCell::dot_number(0, 4), None);
+        assert!(!BrailleCell::FULL.get(2, 0));
+        let mut unchanged = cell;
+        unchanged.set(0, 4);
+        unchanged.clear(5, 1);
+        assert_eq!(unchanged, cell);
+    }
+
+    #[test]
+    fn braille_image_test() {
+        let art = "⣿⠁\n⠀⡀".parse::<BrailleImage>().unwrap();
+        assert_eq!((art.width(), art.height()), (2, 2));
+        assert_eq!(art.to_string(), "⣿⠁\n⠀⡀");
+        assert!("⣿a".parse::<BrailleImage>().is_err());
+        assert_eq!(
+            "⣿⣿\n⣿".parse::<BrailleImage>().unwrap().to_string(),
+            "⣿⣿\n⣿⠀"
+        );
+
+        assert_eq!(art.crop(1, 0, 5, 5).to_string(), "⠁\n⡀");
+        assert_eq!(art.pad(1, 0, 0, 1).to_string(), "⠀⠀⠀\n⠀⣿⠁\n⠀⠀⡀");
+        assert_eq!(
+            art.concat_horizontal(&art.crop(0, 0, 1, 1)).to_string(),
+            "⣿⠁⣿\n⠀⡀⠀"
+        );
+        assert_eq!(
+            art.concat_vertical(&art.crop(0, 0, 1, 1)).to_string(),
+            "⣿⠁\n⠀⡀\n⣿⠀"
+        );
+
+        let top = "⠉".parse::<BrailleImage>().unwrap();
+        assert_eq!(art.overlay(&top, 1, 0, BoolOp::Or).to_string(), "⣿⠉\n⠀⡀");
+        assert_eq!(
+            art.overlay(&top, 0, 0, BoolOp::Subtract).to_string(),
+            "⣶⠁\n⠀⡀"
+        );
+        assert_eq!(art.overlay(&top, 1, 1, BoolOp::Xor).to_string(), "⣿⠁\n⠀⡉");
+
+        // dot level transforms
+        assert_eq!(art.flip_horizontal().to_string(), "⠈⣿\n⢀⠀");
+        assert_eq!(art.flip_vertical().to_string(), "⠀⠁\n⣿⡀");
+        assert_eq!(art.rotate180(), art.flip_horizontal().flip_vertical());
+        assert_eq!(art.rotate90().rotate270(), art);
+        assert_eq!(art.rotate90().rotate90(), art.rotate180());
+        assert_eq!((art.rotate90().width(), art.rotate90().height()), (4, 1));
+
+        let bitmap = art.to_bitmap();
+        assert_eq!(bitmap.dimensions(), (4, 8));
+        assert_eq!(BrailleImage::from_bitmap(&bitmap, 128), art);
+    }
+
+    #[test]
+    fn braille_decode_test() {
+        let chat_message =
+            "\u{FEFF}look at this:\r\n\n ⣿⣿ lol\r\n⡇\u{200B}x⢸\n\nsent from my phone";
+        let art = decode(chat_message);
+        assert_eq!(art.to_string(), "⠀⣿⣿\n⡇⠀⢸");
+
+        let bitmap = decode_to_bitmap(chat_message);
+        assert_eq!(bitmap.dimensions(), (6, 8));
+        assert_eq!(bitmap.get_pixel(2, 0).0, [255]);
+        assert_eq!(bitmap.get_pixel(0, 0).0, [0]);
+
+        assert_eq!(decode("no braille here"), BrailleImage::default());
+    }
+
+    #[test]
+    fn braille_resample_test() {
+        let art = "⣿⠁\n⠀⡀".parse::<BrailleImage>().unwrap();
+
+        // nearest: every dot becomes a 2x2 block, halving gives it back
+        let doubled = art.scale(2.0, ResampleFilter::Nearest).unwrap();
+        assert_eq!((doubled.dots_width(), doubled.dots_height()), (8, 16));
+        assert_eq!(doubled.to_string(), "⣿⣿⠛⠀\n⣿⣿⠀⠀\n⠀⠀⠀⠀\n⠀⠀⣤⠀");
+        assert_eq!(
+            doubled.scale(0.5, ResampleFilter::Nearest),
+            Some(art.clone())
+        );
+        assert_eq!(
+            art.scale(2.0, ResampleFilter::Epx)
+                .and_then(|art| art.scale(0.5, ResampleFilter::Nearest)),
+            Some(art.clone())
+        );
+
+        // too big or meaningless sizes
+        for factor in [0.0, -1.0, f32::NAN, f32::INFINITY, 1e6] {
+            assert_eq!(art.scale(factor, ResampleFilter::Epx), None);
+        }
+        assert_eq!(art.resample(usize::MAX, 2, ResampleFilter::Nearest), None);
+        assert_eq!(
+            art.resample(MAX_RESAMPLED_DOTS + 1, 1, ResampleFilter::Epx),
+            None
+        );
+
+        // voting: a quarter raised is not enough, half is
+        let quarter = "⠁".parse::<BrailleImage>().unwrap();
+        assert_eq!(
+            quarter
+                .resample(1, 2, ResampleFilter::Nearest)
+                .unwrap()
+                .to_string(),
+            "⠀"
+        );
+        let half = "⠃".parse::<BrailleImage>().unwrap();
+        assert_eq!(
+            half.resample(1, 2, ResampleFilter::Nearest)
+                .unwrap()
+                .to_string(),
+            "⠁"
+        );
+
+        // epx smooths diagonals: stairs are filled instead of being made of 2x2 blocks
+        let diagonal = BrailleImage::from_dot_fn(4, 4, |x, y| x == y);
+        let count_raised = |art: &BrailleImage| {
+            (0..art.dots_height())
+                .flat_map(|y| (0..art.dots_width()).map(move |x| (x, y)))
+                .filter(|&(x, y)| art.get_dot(x, y))
+                .count()
+        };
+        assert_eq!(
+            count_raised(&diagonal.resample(8, 8, ResampleFilter::Nearest).unwrap()),
+            16
+        );
+        assert_eq!(
+            count_raised(&diagonal.resample(8, 8, ResampleFilter::Epx).unwrap()),
+            20
+        );
+    }
+
+    #[test]
+    fn eval_metrics_test() {
+        let art = BrailleImage::from_dot_fn(40, 40, |x, y| (x / 5 + y / 5) % 2 == 0);
+        let source = DynamicImage::ImageLuma8(art.to_bitmap());
+
+        let identical = evaluate(&source, &art);
+        assert_eq!(identical.psnr, f64::INFINITY);
+        assert!((identical.ssim - 1.0).abs() < 1e-9);
+        assert_eq!(identical.mean_brightness_error, 0.0);
+
+        let inverted = evaluate(&source, &art.invert());
+        assert!(inverted.psnr < 10.0);
+        assert!(inverted.ssim < 0.0);
+
+        let too_bright = evaluate(&source, &BrailleImage::from_dot_fn(40, 40, |_, _| true));
+        assert!((too_bright.mean_brightness_error - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn braille_render_options_test() {
+        let options = Braille2ImgOptions::builder()
+            .set_bg_color([16, 32, 48])
+            .set_text_color([255, 200, 0])
+            .set_font_size(13.0)
+            .build();
+        // measured on the embedded font
+        assert_eq!((options.char_width, options.char_height), (7, 13));
+
+        let png = braille_to_image("⣿⠀⣿\n⠀⣿⠀", Some(options)).unwrap();
+        let rendered = image::load_from_memory(&png).unwrap().to_rgb8();
+        assert_eq!(rendered.dimensions(), (3 * 7, 2 * 13));
+        assert_eq!(rendered.get_pixel(7, 0).0, [16, 32, 48]);
+        assert!(rendered.pixels().any(|p| p.0 == [255, 200, 0]));
+
+        let too_small = Braille2ImgOptions::builder().set_font_size(0.0).build();
+        assert!(braille_to_image("⣿", Some(too_small)).is_err());
+    }
+
+    #[test]
+    fn custom_font_test() {
+        assert!(CustomFont::from_bytes(b"not a font".to_vec()).is_err());
+
+        // no braille glyphs: the width comes from the embedded font, the line height from the custom one
+        let font_data = include_bytes!("../fonts/DejaVuSansMono.ttf").to_vec();
+        let options = Braille2ImgOptions::builder()
+            .set_font(CustomFont::from_bytes(font_data).unwrap())
+            .build();
+        assert_eq!((options.char_width, options.char_height), (10, 20));
+
+        // the braille character is drawn with the embedded font, the letter with the custom one
+        let png = braille_to_image("⣿a", Some(options)).unwrap();
+        let rendered = image::load_from_memory(&png).unwrap().to_luma8();
+        assert_eq!(rendered.dimensions(), (2 * 10, 20));
+        let has_ink = |cell: u32| {
+            (cell * 10..(cell + 1) * 10)
+                .any(|x| (0..20).any(|y| rendered.get_pixel(x, y).0[0] > 128))
+        };
+        assert!(has_ink(0) && has_ink(1));
+    }
+
+    #[test]
+    fn glyph_atlas_test() {
+        // every pattern, on ragged lines
+        let art = BrailleImage::from_cells(
+            (0..16)
+                .map(|line| {
+                    (0..=line * 2)
+                        .map(|i| BrailleCell((line * 16 + i) as u8))
+                        .collect()
+                })
+                .collect(),
+        );
+        let decode = |png: Vec<u8>| image::load_from_memory(&png).unwrap().to_rgb8();
+
+        let font_data = include_bytes!("../fonts/DejaVuSansMono.ttf").to_vec();
+        let custom_font = CustomFont::from_bytes(font_data).unwrap();
+        let embedded = embedded_font();
+        for (options, font, fallback) in [
+            (Braille2ImgOptions::default(), &embedded, None),
+            (
+                Braille2ImgOptions::builder()
+                    .set_font_size(13.0)
+                    .set_bg_color([16, 32, 48])
+                    .set_text_color([255, 200, 0])
+                    .build(),
+                &embedded,
+                None,
+            ),
+            (
+                Braille2ImgOptions::builder()
+                    .set_font(custom_font.clone())
+                    .build(),
+                &custom_font.0,
+                Some(&embedded),
+            ),
+            (
+                Braille2ImgOptions::builder()
+                    .set_margin(5)
+                    .set_line_spacing(3)
+                    .set_letter_spacing(2)
+                    .build(),
+                &embedded,
+                None,
+            ),
+        ] {
+            let drawn = decode(text_to_image(&art.to_string(), &options, font, fallback).unwrap());
+            // twice: the second render uses the cached atlas
+            for _ in 0..2 {
+                let blitted = decode(braille_art_to_image(&art, Some(options.clone())).unwrap());
+                assert!(drawn == blitted);
+            }
+        }
+    }
+
+    #[test]
+    fn glyph_atlas_font_swap_test() {
+        let art = BrailleImage::from_cells(vec![vec![BrailleCell(u8::MAX); 3]; 2]);
+        let decode = |png: Vec<u8>| image::load_from_memory(&png).unwrap().to_rgb8();
+        let render = |options: &Braille2ImgOptions| {
+            decode(braille_art_to_image(&art, Some(options.clone())).unwrap())
+        };
+
+        // a single glyph font: '⣿' is a plain square
+        let font_data = include_bytes!("../fonts/test-square.ttf").to_vec();
+        let square_font = CustomFont::from_bytes(font_data).unwrap();
+        let options = Braille2ImgOptions::builder()
+            .set_font(square_font.clone())
+            .build();
+        let with_square = render(&options);
+
+        // the clone shares the atlas built for the square font
+        let mut swapped = options.clone();
+        swapped.font = None;
+        let with_embedded = render(&swapped);
+        assert!(with_embedded != with_square);
+        let fresh = Braille2ImgOptions {
+            atlas: Default::default(),
+            ..swapped.clone()
+        };
+        assert!(with_embedded == render(&fresh));
+
+        // an other load of the same file is an other font
+        let mut reloaded = swapped.clone();
+        reloaded.font = Some(CustomFont::from_bytes(square_font.data().to_vec()).unwrap());
+        assert!(render(&reloaded) == with_square);
+    }
+
+    #[test]
+    fn text_layout_test() {
+        let layout = TextLayout::new("ab\n漢\tx\ne\u{301}\u{7}");
+        assert_eq!(layout.columns, 5);
+        let columns = |line: usize| {
+            layout.lines[line]
+                .iter()
+                .map(|placed| (placed.character, placed.column, placed.width))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(columns(0), [('a', 0, 1), ('b', 1, 1)]);
+        // the tab goes to the next tab stop
+        assert_eq!(columns(1), [('漢', 0, 2), ('x', 4, 1)]);
+        // the combining accent is drawn over its letter, the control character is dropped
+        assert_eq!(columns(2), [('e', 0, 1), ('\u{301}', 0, 0)]);
+
+        // sized from the widest line, not the first one
+        let options = Braille2ImgOptions::default();
+        let font = embedded_font();
+        let ragged = draw_text::<Rgb<u8>>("⣿\n⣿漢⣿⣿", &options, &font, None).unwrap();
+        assert_eq!(ragged.dimensions(), (5 * 10, 2 * 20));
+        let last_cell = |image: &image::RgbImage| {
+            (40..50).any(|x| (20..40).any(|y| image.get_pixel(x, y).0 != [0, 0, 0]))
+        };
+        assert!(last_cell(&ragged));
+
+        let spaced = Braille2ImgOptions::builder()
+            .set_margin(4)
+            .set_line_spacing(6)
+            .set_letter_spacing(2)
+            .build();
+        let image = braille_to_rgb_image("⣿⣿⣿\n⣿", Some(spaced.clone())).unwrap();
+        assert_eq!(
+            image.dimensions(),
+            (4 + 3 * 10 + 2 * 2 + 4, 4 + 2 * 20 + 6 + 4)
+        );
+        assert!((0..image.width()).all(|x| image.get_pixel(x, 2).0 == [0, 0, 0]));
+        let text = draw_text::<Rgb<u8>>("⣿⣿⣿\n⣿", &spaced, &font, None).unwrap();
+        assert!(text == image);
+
+        let dots = Braille2ImgOptions {
+            renderer: Renderer::Dots(DotStyle::default()),
+            char_width: 9,
+            char_height: 17,
+            ..spaced
+        };
+        let image = braille_to_rgb_image("⣿⣿⣿\n⣿", Some(dots)).unwrap();
+        assert_eq!(
+            image.dimensions(),
+            (4 + 3 * 9 + 2 * 2 + 4, 4 + 2 * 17 + 6 + 4)
+        );
+        // first dot of the second character, after the margin, a cell, the letter spacing and the cell padding
+        assert_eq!(
+            image.get_pixel(4 + 9 + 2 + 1 + 1, 4 + 1 + 1).0,
+            [255, 255, 255]
+        );
+    }
+
+    #[test]
+    fn themed_rendering_test() {
+        let themed = Braille2ImgOptions::builder()
+            .set_theme(Theme::AmberCrt)
+            .set_bg_color([1, 2, 3])
+            .build();
+        assert_eq!(
+            (themed.background_color, themed.text_color, themed.effect),
+            (
+                [1, 2, 3],
+                Theme::AmberCrt.colors().1,
+                Theme::AmberCrt.effect()
+            )
+        );
+
+        // 2x2 characters, every pixel covered by a dot
+        let full = BrailleImage::from_dot_fn(4, 8, |_, _| true);
+        let solid = |gradient: Gradient| {
+            Braille2ImgOptions::builder()
+                .set_text_color([255, 0, 0])
+                .set_gradient(gradient)
+                .set_renderer(Renderer::Dots(DotStyle {
+                    shape: DotShape::Square,
+                    diameter: 2,
+                    spacing: 0,
+                    cell_padding: 0,
+                }))
+                .build()
+        };
+        let vertical =
+            braille_art_to_rgb_image(&full, Some(solid(Gradient::Vertical([0, 0, 255])))).unwrap();
+        assert_eq!(vertical.dimensions(), (8, 16));
+        assert_eq!(vertical.get_pixel(7, 0).0, [255, 0, 0]);
+        assert_eq!(vertical.get_pixel(0, 15).0, [0, 0, 255]);
+        assert_eq!(vertical.get_pixel(0, 5).0, [170, 0, 85]);
+        let horizontal =
+            braille_art_to_rgb_image(&full, Some(solid(Gradient::Horizontal([0, 0, 255]))))
+                .unwrap();
+        assert_eq!(horizontal.get_pixel(0, 15).0, [255, 0, 0]);
+        assert_eq!(horizontal.get_pixel(7, 0).0, [0, 0, 255]);
+        let radial =
+            braille_art_to_rgb_image(&full, Some(solid(Gradient::Radial([0, 0, 255])))).unwrap();
+        assert!(radial.get_pixel(4, 8).0[0] > 200 && radial.get_pixel(0, 0).0[2] > 200);
+
+        // with a transparent background the gradient is in the colors, the coverage in the alpha
+        let transparent = Braille2ImgOptions {
+            transparent_background: true,
+            ..solid(Gradient::Vertical([0, 0, 255]))
+        };
+        let rgba = braille_art_to_rgba_image(&"⠁".parse().unwrap(), Some(transparent)).unwrap();
+        assert_eq!(rgba.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(rgba.get_pixel(0, 7).0, [0, 0, 255, 0]);
+
+        // the glow lights the background around the dot, the scanlines dim the odd rows
+        let dot: BrailleImage = "⠀⠀⠀\n⠀⠂⠀\n⠀⠀⠀".parse().unwrap();
+        let plain = braille_art_to_rgb_image(&dot, None).unwrap();
+        let effect = |glow: f32, scanlines: bool| {
+            let options = Braille2ImgOptions::builder()
+                .set_effect(CrtEffect { glow, scanlines })
+                .build();
+            braille_art_to_rgb_image(&dot, Some(options)).unwrap()
+        };
+        let lit = |image: &image::RgbImage| image.pixels().filter(|p| p.0 != [0, 0, 0]).count();
+        assert!(effect(0.0, false) == plain);
+        assert!(lit(&effect(2.0, false)) > lit(&plain));
+        let scanlines = effect(0.0, true);
+        for (x, y, pixel) in plain.enumerate_pixels() {
+            let dimmed = scanlines.get_pixel(x, y).0[0];
+            match y % 2 {
+                0 => assert!(pixel.0[0].abs_diff(dimmed) <= 1),
+                _ => assert!(dimmed <= pixel.0[0]),
+            }
+        }
+        assert!(scanlines
+            .pixels()
+            .zip(plain.pixels())
+            .any(|(a, b)| a.0[0] < b.0[0]));
+
+        let svg = braille_art_to_svg(
+            &full,
+            Some(solid(Gradient::Radial([0, 0, 255]))),
+            SvgFont::Embed,
+        );
+        assert!(svg.contains("<radialGradient") && svg.contains(r#"fill="url(#vidascii-text)""#));
+    }
+
+    #[test]
+    fn html_output_test() {
+        let art: BrailleImage = "⣿⣿⠀⣿\n⠁⠂⠀⠀".parse().unwrap();
+        let (red, blue, green) = ([255, 0, 0], [0, 0, 255], [0, 255, 0]);
+        let colors = vec![vec![red, red, blue, green], vec![[255, 255, 255]; 4]];
+
+        let html = braille_art_to_html(&art, Some(&colors), None);
+        assert!(html.starts_with("<!DOCTYPE html>") && html.ends_with("</html>\n"));
+        // self-contained, with the 10x20 cells of the png output
+        assert!(html.contains("src:url(\"data:font/ttf;base64,"));
+        assert!(html.contains("font-size:20px;line-height:20px"));
+        // the blank character joins the red run, the white line is the default text color
+        assert!(html.contains(
+            "<pre><span style=\"color:#ff0000\">⣿⣿⠀</span><span style=\"color:#00ff00\">⣿</span>\n⠁⠂⠀⠀</pre>"
+        ));
+
+        let plain = braille_art_to_html(&art, None, None);
+        assert!(plain.contains("<pre>⣿⣿⠀⣿\n⠁⠂⠀⠀</pre>") && !plain.contains("<span"));
+
+        // left half red, right half blue
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_fn(16, 8, |x, _| {
+            image::Rgb(if x < 8 { red } else { blue })
+        }));
+        let art =
+            dynamic_image_to_braille_art(img.clone(), None, None::<fn(&'static str, u8)>).unwrap();
+        let colors = cell_colors(&img, &art);
+        assert_eq!((colors.len(), colors[0].len()), (2, 8));
+        assert_eq!((colors[1][0], colors[1][7]), (red, blue));
+    }
+
+    #[test]
+    fn output_size_test() {
+        let art = BrailleImage::from_dot_fn(6, 8, |x, y| (x + y) % 3 == 0);
+        let sized = |size: OutputSize, renderer: Renderer| {
+            Braille2ImgOptions::builder()
+                .set_size(size)
+                .set_renderer(renderer)
+                .build()
+        };
+
+        let pixels = sized(OutputSize::Pixels(61, 33), Renderer::Font);
+        let image = braille_art_to_rgb_image(&art, Some(pixels.clone())).unwrap();
+        assert_eq!(image.dimensions(), (61, 33));
+        assert!(image.pixels().any(|p| p.0 != [0, 0, 0]));
+        // the atlas of the fitted font size is rasterized once, in the cache shared by the clones
+        braille_art_to_rgb_image(&art, Some(pixels.clone())).unwrap();
+        assert_eq!(format!("{:?}", pixels.atlas), "GlyphAtlasCache(1)");
+        assert!(braille_art_to_rgb_image(
+            &art,
+            Some(sized(OutputSize::Pixels(0, 10), Renderer::Font))
+        )
+        .is_err());
+
+        // one pixel per dot: the dots shrink to 1px without gap, no resizing needed
+        let source = sized(OutputSize::Source, Renderer::Dots(DotStyle::default()));
+        let image = braille_art_to_rgb_image(&art, Some(source)).unwrap();
+        assert_eq!(image.dimensions(), (6, 8));
+        for (x, y, pixel) in art.to_bitmap().enumerate_pixels() {
+            assert_eq!(image.get_pixel(x, y).0, [pixel.0[0]; 3]);
+        }
+
+        // the size of the converted image, whatever the ratio
+        let mut png = vec![];
+        DynamicImage::ImageRgb8(image::RgbImage::from_pixel(50, 30, image::Rgb([200; 3])))
+            .write_to(
+                &mut std::io::Cursor::new(&mut png),
+                image::ImageOutputFormat::Png,
+            )
+            .unwrap();
+        let options = Img2BrailleOptions::builder().set_ratio(2.0).build();
+        let rendered = image_to_braille(
+            &png,
+            Some(options),
+            Some(sized(OutputSize::Source, Renderer::Font)),
+            None::<fn(&'static str, u8)>,
+        )
+        .unwrap();
+        let rendered = image::load_from_memory(&rendered).unwrap();
+        assert_eq!((rendered.width(), rendered.height()), (50, 30));
+
+        // scaled by the viewer
+        let svg = braille_art_to_svg(&art, Some(pixels), SvgFont::Embed);
+        assert!(svg.contains(r#"width="61" height="33" viewBox="0 0 30 40""#));
+    }
+
+    #[test]
+    fn comparison_test() {
+        let art = BrailleImage::from_dot_fn(6, 8, |x, y| (x + y) % 3 == 0);
+        let source = DynamicImage::ImageRgb8(image::RgbImage::from_fn(40, 24, |x, y| {
+            image::Rgb([x as u8 * 6, y as u8 * 10, 120])
+        }));
+        let compared = |comparison: Comparison| {
+            let options = Braille2ImgOptions::builder()
+                .set_comparison(comparison)
+                .set_format(ImageFormat::RawRgba)
+                .build();
+            let raw = braille_art_comparison_to_image(&source, &art, Some(options)).unwrap();
+            image::RgbaImage::from_raw(raw.len() as u32 / 4 / 24, 24, raw).unwrap()
+        };
+        let rendering = braille_art_to_rgb_image(
+            &art,
+            Some(
+                Braille2ImgOptions::builder()
+                    .set_size(OutputSize::Pixels(40, 24))
+                    .build(),
+            ),
+        )
+        .unwrap();
+
+        // source on the left, rendering at the source size on the right
+        let side_by_side = compared(Comparison::SideBySide);
+        assert_eq!(side_by_side.dimensions(), (80, 24));
+        for (x, y, pixel) in source.to_rgb8().enumerate_pixels() {
+            assert_eq!(side_by_side.get_pixel(x, y).0[..3], pixel.0);
+            assert_eq!(
+                side_by_side.get_pixel(x + 40, y).0[..3],
+                rendering.get_pixel(x, y).0
+            );
+        }
+
+        let transparent = compared(Comparison::Overlay(0.0));
+        let opaque = compared(Comparison::Overlay(1.0));
+        assert_eq!(opaque.dimensions(), (40, 24));
+        for (x, y, pixel) in source.to_rgb8().enumerate_pixels() {
+            assert_eq!(transparent.get_pixel(x, y).0[..3], pixel.0);
+            assert_eq!(opaque.get_pixel(x, y).0[..3], rendering.get_pixel(x, y).0);
+        }
+    }
+
+    #[test]
+    fn image_formats_test() {
+        let art = BrailleImage::from_dot_fn(20, 12, |x, y| (x + y) % 3 == 0);
+        let options = Braille2ImgOptions::builder()
+            .set_text_color([255, 200, 0])
+            .build();
+        let rgb = braille_art_to_rgb_image(&art, Some(options.clone())).unwrap();
+        assert_eq!(rgb.dimensions(), (10 * 10, 3 * 20));
+
+        let encode = |format: ImageFormat| {
+            let options = Braille2ImgOptions {
+                format,
+                ..options.clone()
+            };
+            braille_art_to_image(&art, Some(options)).unwrap()
+        };
+
+        // lossless
+        for format in [
+            ImageFormat::Png(PngCompression::Fast),
+            ImageFormat::Png(PngCompression::Default),
+            ImageFormat::Png(PngCompression::Best),
+            ImageFormat::WebP,
+            ImageFormat::Bmp,
+        ] {
+            let decoded = image::load_from_memory(&encode(format)).unwrap().to_rgb8();
+            assert!(decoded == rgb, "{format:?}");
+        }
+
+        let jpeg = image::load_from_memory(&encode(ImageFormat::Jpeg(90))).unwrap();
+        assert_eq!(
+            (jpeg.width(), jpeg.height(), jpeg.color()),
+            (100, 60, image::ColorType::Rgb8)
+        );
+
+        let rgba = braille_art_to_rgba_image(&art, Some(options.clone())).unwrap();
+        assert!(rgba.pixels().all(|p| p.0[3] == 255));
+        assert_eq!(encode(ImageFormat::RawRgba), rgba.into_raw());
+    }
+
+    #[test]
+    fn transparent_background_test() {
+        let art: BrailleImage = "⣿⠁\n⠀⢀".parse().unwrap();
+        let options = Braille2ImgOptions::builder()
+            .set_bg_color([0, 0, 255])
+            .set_text_color([255, 200, 0])
+            .set_font_size(40.0)
+            .set_transparent_background(true)
+            .build();
+
+        // same coverage as the opaque rendering, moved to the alpha channel
+        let opaque = braille_art_to_rgb_image(&art, Some(options.clone())).unwrap();
+        let rgba = braille_art_to_rgba_image(&art, Some(options.clone())).unwrap();
+        assert!(opaque.pixels().all(|p| p.0 != [0, 0, 0]));
+        let alphas = rgba.pixels().map(|p| p.0[3]).collect::<Vec<_>>();
+        assert!(alphas.contains(&0) && alphas.contains(&255));
+        assert!(alphas.iter().any(|&alpha| alpha > 0 && alpha < 255));
+        for (opaque, transparent) in opaque.pixels().zip(rgba.pixels()) {
+            // blending the text color over itself, up to rounding
+            let [r, g, b, _] = transparent.0;
+            assert!(r >= 254 && g.abs_diff(200) <= 1 && b == 0);
+            // blue channel: 255 on the background, 0 on the glyph cores
+            assert!(opaque.0[2].abs_diff(255 - transparent.0[3]) <= 1);
+        }
+
+        let png = braille_art_to_image(&art, Some(options.clone())).unwrap();
+        assert_eq!(image::load_from_memory(&png).unwrap().to_rgba8(), rgba);
+        let webp = Braille2ImgOptions {
+            format: ImageFormat::WebP,
+            ..options.clone()
+        };
+        let webp = braille_art_to_image(&art, Some(webp)).unwrap();
+        assert_eq!(image::load_from_memory(&webp).unwrap().to_rgba8(), rgba);
+
+        // no alpha channel in jpeg
+        let jpeg = Braille2ImgOptions {
+            format: ImageFormat::Jpeg(90),
+            ..options.clone()
+        };
+        let jpeg = braille_art_to_image(&art, Some(jpeg)).unwrap();
+        assert_eq!(
+            image::load_from_memory(&jpeg).unwrap().color(),
+            image::ColorType::Rgb8
+        );
+
+        let dots = Braille2ImgOptions::builder()
+            .set_renderer(Renderer::Dots(DotStyle::default()))
+            .set_transparent_background(true)
+            .build();
+        let rgba = braille_art_to_rgba_image(&art, Some(dots.clone())).unwrap();
+        assert_eq!(rgba.get_pixel(0, 0).0[3], 0);
+        assert_eq!(rgba.get_pixel(2, 2).0, [255, 255, 255, 255]);
+
+        let svg = braille_art_to_svg(&art, Some(dots), SvgFont::Embed);
+        assert!(!svg.contains(r#"width="100%""#));
+    }
+
+    #[test]
+    fn svg_output_test() {
+        assert_eq!(base64_encode(b"Man"), "TWFu");
+        assert_eq!(base64_encode(b"Ma"), "TWE=");
+        assert_eq!(base64_encode(b"M"), "TQ==");
+
+        let art: BrailleImage = "⣿⠁\n⠀⢀".parse().unwrap();
+        let options = Braille2ImgOptions::builder()
+            .set_text_color([255, 200, 0])
+            .build();
+
+        let svg = braille_art_to_svg(&art, Some(options.clone()), SvgFont::Embed);
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="40""#)
+        );
+        assert_eq!(svg.matches("<text ").count(), 2);
+        assert!(svg.contains(">⣿⠁</text>") && svg.contains(">⠀⢀</text>"));
+        assert!(svg.contains("fill:#ffc800") && svg.contains("data:font/ttf;base64,"));
+
+        let svg = braille_art_to_svg(
+            &art,
+            Some(options),
+            SvgFont::Link("https://example.com/font.ttf?a=1&b=\"2\"".to_string()),
+        );
+        assert!(svg.contains(r#"url("https://example.com/font.ttf?a=1&amp;b=%222%22")"#));
+        assert!(!svg.contains("base64"));
+
+        for (shape, element) in [
+            (DotShape::Circle, "<circle "),
+            (DotShape::Square, "<rect x="),
+        ] {
+            let options = Braille2ImgOptions::builder()
+                .set_renderer(Renderer::Dots(DotStyle {
+                    shape,
+                    ..Default::default()
+                }))
+                .build();
+            let svg = braille_art_to_svg(&art, Some(options), SvgFont::Embed);
+            assert_eq!(svg.matches(element).count(), 8 + 1 + 1);
+            assert!(!svg.contains("<text"));
+        }
+    }
+
+    #[test]
+    fn dot_renderer_test() {
+        let render = |text: &str, shape: DotShape| {
+            let style = DotStyle {
+                shape,
+                diameter: 3,
+                spacing: 1,
+                cell_padding: 1,
+            };
+            let options = Braille2ImgOptions::builder()
+                .set_renderer(Renderer::Dots(style))
+                .build();
+            assert_eq!((options.char_width, options.char_height), (9, 17));
+            let png = braille_to_image(text, Some(options)).unwrap();
+            image::load_from_memory(&png).unwrap().to_rgb8()
+        };
+
+        // dot 1 of the second character, dot 8 of the first one
+        let rendered = render("⢀⠁", DotShape::Square);
+        assert_eq!(rendered.dimensions(), (2 * 9, 17));
+        let is_raised = |x: u32, y: u32| rendered.get_pixel(x, y).0 == [255, 255, 255];
+        assert!((10..13).all(|x| (1..4).all(|y| is_raised(x, y))));
+        assert!((5..8).all(|x| (13..16).all(|y| is_raised(x, y))));
+        assert!(!is_raised(9, 0) && !is_raised(13, 1) && !is_raised(0, 0));
+        assert_eq!(
+            rendered.pixels().filter(|p| p.0 == [255, 255, 255]).count(),
+            2 * 9
+        );
+
+        // circle corners are left empty
+        let rendered = render("⠁", DotShape::Circle);
+        assert_eq!(rendered.get_pixel(1, 1).0, [0, 0, 0]);
+        assert_eq!(rendered.get_pixel(2, 2).0, [255, 255, 255]);
+        assert_eq!(
+            rendered.pixels().filter(|p| p.0 == [255, 255, 255]).count(),
+            5
+        );
+
+        let empty = BrailleImage::new(0, 0);
+        assert!(braille_art_to_image(&empty, None).is_err());
+    }
+
+    #[test]
+    fn auto_preset_search_test() {
+        // horizontal gradient
+        let source = DynamicImage::ImageLuma8(GrayImage::from_fn(64, 64, |x, _| {
+            Luma([(x * 255 / 63) as u8])
+        }));
+
+        let (best_options, best_report) = find_best_options(&source, 1.0).unwrap();
+        assert_eq!(best_options.ratio, 1.0);
+
+        // at least as good as the default settings
+        let default_art = dynamic_image_to_braille_art(
+            source.clone(),
+            Some(Img2BrailleOptions::default()),
+            None::<fn(&'static str, u8)>,
+        )
+        .unwrap();
+        assert!(best_report.ssim >= evaluate(&source, &default_art).ssim);
+
+        // the report matches the returned options
+        let best_art = dynamic_image_to_braille_art(
+            source.clone(),
+            Some(best_options),
+            None::<fn(&'static str, u8)>,
+        )
+        .unwrap();
+        assert_eq!(evaluate(&source, &best_art), best_report);
+    }
+
+    #[test]
+    fn default_grayscale_test() {
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_fn(6, 4, |x, y| {
+            Rgb([
+                (x * 50 % 256) as u8,
+                ((y * 70 + x * 30) % 256) as u8,
+                (x * y * 40 % 256) as u8,
+            ])
+        }));
+        let convert = |builder: Img2BrailleOptionsBuilder| {
+            dynamic_image_to_braille_art(
+                img.clone(),
+                Some(builder.set_ratio(0.5).set_dithering(false).build()),
+                None::<fn(&'static str, u8)>,
+            )
+            .unwrap()
+            .to_string()
+        };
+
+        // output of the conversion before the grayscale option, luminance when a pixel spans several dots
+        assert_eq!(convert(Img2BrailleOptions::builder()), "⠀⠀⠀⣤⣿⣿\n⣤⣿⠛⠛⠀⣤");
+        assert_ne!(
+            convert(Img2BrailleOptions::builder().set_grayscale(GrayScaleMode::Average)),
+            "⠀⠀⠀⣤⣿⣿\n⣤⣿⠛⠛⠀⣤"
+        );
+    }
+
+    #[test]
+    fn error_minimizing_patterns_test() {
+        let white = select_patterns(&vec![vec![[[255_u16; 4]; 2]; 8]; 8]);
+        assert!(white.iter().flatten().all(|&c| c == BrailleCell::FULL));
+
+        // no pixels: never raised
+        let transparent = select_patterns(&vec![vec![[[256_u16; 4]; 2]; 8]; 8]);
+        assert!(transparent
+            .iter()
+            .flatten()
+            .all(|&c| c == BrailleCell::EMPTY));
+
+        // mid-gray: about half of the dots are raised
+        let gray = select_patterns(&vec![vec![[[128_u16; 4]; 2]; 8]; 8]);
+        let raised = gray.iter().flatten().map(|c| c.count()).sum::<u32>();
+        assert!(
+            (224..=288).contains(&raised),
+            "{raised} dots raised out of 512"
+        );
+    }
+
+    #[test]
+    fn ascii_shape_matching_test() {
+        let font = load_ascii_font().unwrap();
+        let glyph_set = GlyphSet::rasterize(&font, &Img2AsciiOptions::default().charset).unwrap();
+
+        // one 8x16 cell: empty, then a vertical bar in the middle
+        let blank = GrayImage::new(8, 16);
+        let vertical_bar = GrayImage::from_fn(8, 16, |x, _| match x {
+            3 | 4 => Luma([255]),
+            _ => Luma([0]),
+        });
+        for metric in [MatchMetric::Ssd, MatchMetric::Ssim] {
+            let options = Img2AsciiOptions::builder().set_metric(metric).build();
+            assert_eq!(match_glyphs(&blank, 4.0, &glyph_set, &options), " ");
+            assert_eq!(match_glyphs(&vertical_bar, 4.0, &glyph_set, &options), "|");
+        }
+    }
 
     #[test]
     fn braille_pixels_to_string_bench_test() {
@@ -111,6 +1012,157 @@ mod core_tests {
         assert!(avg_elapsed < 1000)
     }
 
+    #[test]
+    fn frame_pool_test() {
+        const FRAME_COUNT: u32 = 23;
+        const BAND_WIDTH: u32 = 4;
+        let decoded = Cell::new(0_usize);
+        // frame i only lights the band i, and counts how many frames were handed out
+        let frames = (0..FRAME_COUNT).map(|i| {
+            decoded.set(decoded.get() + 1);
+            let image = image::RgbImage::from_fn(FRAME_COUNT * BAND_WIDTH, 8, |x, _| {
+                Rgb([if x / BAND_WIDTH == i { 255 } else { 0 }; 3])
+            });
+            OutputVideoFrame {
+                width: image.width(),
+                height: image.height(),
+                pix_fmt: "rgb24".to_string(),
+                output_index: 0,
+                data: image.into_raw(),
+                frame_num: i,
+                timestamp: 0.0,
+            }
+        });
+        let render_options = Some(Braille2ImgOptions::builder().build());
+
+        let mut written = vec![];
+        convert_frames(
+            frames.clone(),
+            3,
+            Img2BrailleOptions::default(),
+            &render_options,
+            |converted| {
+                // never more than twice the jobs ahead of the writer
+                assert!(decoded.get() - written.len() <= 6);
+                written.push(converted);
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(written.len(), FRAME_COUNT as usize);
+        for (i, frame) in written.iter().enumerate() {
+            let band_width = frame.width() / FRAME_COUNT;
+            let brightest = (0..FRAME_COUNT as usize)
+                .max_by_key(|band| {
+                    frame
+                        .enumerate_pixels()
+                        .filter(|(x, _, _)| (x / band_width) as usize == *band)
+                        .map(|(_, _, pixel)| pixel.0[0] as u64)
+                        .sum::<u64>()
+                })
+                .unwrap();
+            assert_eq!(brightest, i, "frame {i} is out of order");
+        }
+
+        // a broken frame stops the conversion, the frames before it are written
+        decoded.set(0);
+        let broken = frames.map(|mut frame| {
+            if frame.frame_num == 5 {
+                frame.data.truncate(3);
+            }
+            frame
+        });
+        let mut written = 0;
+        let converted = convert_frames(
+            broken,
+            4,
+            Img2BrailleOptions::default(),
+            &render_options,
+            |_| {
+                written += 1;
+                Ok(())
+            },
+        );
+        assert!(matches!(converted, Err(CoreError::StreamError)));
+        assert_eq!(written, 5);
+    }
+
+    #[test]
+    fn video_frame_order_test() {
+        const FRAME_COUNT: u32 = 16;
+        const BAND_WIDTH: u32 = 8;
+        ffmpeg_sidecar::download::auto_download().unwrap();
+
+        let app_path = std::env::temp_dir()
+            .join(format!("vidascii_frame_order_{}", std::process::id()))
+            .display()
+            .to_string();
+        fs::create_dir_all(&app_path).unwrap();
+        defer! {
+            let _ = fs::remove_dir_all(&app_path);
+        }
+        // frame i only lights the band i
+        for i in 0..FRAME_COUNT {
+            image::GrayImage::from_fn(FRAME_COUNT * BAND_WIDTH, 32, |x, _| {
+                Luma([if x / BAND_WIDTH == i { 255 } else { 0 }])
+            })
+            .save(format!("{app_path}/source{i:02}.png"))
+            .unwrap();
+        }
+        let clip = format!("{app_path}/clip.mp4");
+        let encoded = FfmpegCommand::new()
+            .args(["-framerate", "12", "-start_number", "0"])
+            .input(format!("{app_path}/source%02d.png"))
+            .args(["-qp", "0", "-pix_fmt", "yuv444p"])
+            .codec_video("libx264")
+            .overwrite()
+            .output(&clip)
+            .spawn()
+            .unwrap()
+            .wait()
+            .unwrap();
+        assert!(encoded.success());
+
+        let video_options = Vid2BrailleOptions::builder()
+            .set_frame_rate(FrameRate::Fixed(12.0))
+            .build();
+        video_to_braille(
+            Path::new(&clip),
+            Path::new(&app_path),
+            None,
+            None,
+            Some(video_options),
+            |_, _| {},
+        )
+        .unwrap();
+
+        let decoded = FfmpegCommand::new()
+            .input(format!("{app_path}/output.mp4"))
+            .output(format!("{app_path}/converted%d.png"))
+            .spawn()
+            .unwrap()
+            .wait()
+            .unwrap();
+        assert!(decoded.success());
+        for i in 0..FRAME_COUNT {
+            let frame = image::open(format!("{app_path}/converted{}.png", i + 1))
+                .unwrap()
+                .to_luma8();
+            let band_width = frame.width() / FRAME_COUNT;
+            let brightest = (0..FRAME_COUNT)
+                .max_by_key(|band| {
+                    frame
+                        .enumerate_pixels()
+                        .filter(|(x, _, _)| x / band_width == *band)
+                        .map(|(_, _, pixel)| pixel.0[0] as u64)
+                        .sum::<u64>()
+                })
+                .unwrap();
+            assert_eq!(brightest, i, "frame {i} is out of order");
+        }
+        assert!(!Path::new(&format!("{app_path}/converted{}.png", FRAME_COUNT + 1)).exists());
+    }
+
     #[test]
     fn get_frame_count_bench_test() {
         const REAL_FRAME_COUNT: usize = 2001;
@@ -269,4 +1321,57 @@ mod core_tests {
         println!("avg_single_threaded_while_elapsed - {avg_single_threaded_while_elapsed}ms");
         println!("avg_parallel_elapsed - {avg_parallel_elapsed}ms");
     }
+
+    #[test]
+    #[ignore = "benchmark"]
+    fn braille_render_bench_test() {
+        const FRAME_COUNT: u32 = 2000;
+        // a moving pattern, so that every frame has different cells
+        let frames = (0..FRAME_COUNT)
+            .map(|i| {
+                let frame = image::RgbImage::from_fn(320, 240, |x, y| {
+                    let wave = ((x + i) as f32 / 12.0).sin() + ((y + 2 * i) as f32 / 9.0).cos();
+                    image::Rgb([((wave + 2.0) * 63.0) as u8, (x ^ y) as u8, i as u8])
+                });
+                let mut png = vec![];
+                DynamicImage::ImageRgb8(frame)
+                    .write_to(
+                        &mut std::io::Cursor::new(&mut png),
+                        image::ImageOutputFormat::Png,
+                    )
+                    .unwrap();
+                png
+            })
+            .collect::<Vec<_>>();
+        let arts = frames
+            .iter()
+            .map(|frame| image_to_braille_art(frame, None, None::<fn(&'static str, u8)>).unwrap())
+            .collect::<Vec<_>>();
+
+        // rasterization only, both images are encoded the same way
+        fn draw_text_mut(arts: &[BrailleImage]) {
+            let (options, font) = (Braille2ImgOptions::default(), embedded_font());
+            for art in arts {
+                draw_text::<Rgb<u8>>(&art.to_string(), &options, &font, None).unwrap();
+            }
+        }
+
+        fn atlas(arts: &[BrailleImage]) {
+            let options = Braille2ImgOptions::default();
+            for art in arts {
+                render_with_atlas::<Rgb<u8>>(art, &options);
+            }
+        }
+
+        let now = Instant::now();
+        draw_text_mut(&arts);
+        let draw_text_elapsed = now.elapsed().as_millis();
+        let now = Instant::now();
+        atlas(&arts);
+        let atlas_elapsed = now.elapsed().as_millis();
+
+        println!("draw_text_elapsed - {draw_text_elapsed}ms");
+        println!("atlas_elapsed - {atlas_elapsed}ms");
+        assert!(atlas_elapsed < draw_text_elapsed);
+    }
 }
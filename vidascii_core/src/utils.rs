@@ -1,16 +1,46 @@
-use std::fs;
+use std::io::Cursor;
 
-use uuid::Uuid;
+use image::{io::Reader as ImageReader, DynamicImage, GenericImageView, GrayImage, Luma, Rgba};
 
-use crate::CoreError;
+use crate::{
+    img2braille::{compute_brightness, GrayScaleMode},
+    CoreError,
+};
 
-pub fn open_app_path() -> Result<String, CoreError> {
-    let temp_dir = std::env::temp_dir();
-    let session_id = Uuid::new_v4().to_string();
-    let app_path = format!(
-        "{}/vidascii_tmp/{session_id}",
-        temp_dir.display().to_string().trim_end_matches('/')
-    );
-    fs::create_dir_all(&app_path).map_err(|_| CoreError::FailedToOpenAppPath)?;
-    Ok(app_path)
+pub fn decode_image(image_bytes: &[u8]) -> Result<DynamicImage, CoreError> {
+    ImageReader::new(Cursor::new(image_bytes))
+        .with_guessed_format()
+        .map_err(|_| CoreError::WrongExtension)?
+        .decode()
+        .map_err(|_| CoreError::FrameDecodeError)
+}
+
+/// luminance, transparent pixels are considered black
+pub fn to_grayscale(img: &DynamicImage) -> GrayImage {
+    let mut gray = GrayImage::new(img.width(), img.height());
+    for (x, y, Rgba([r, g, b, a])) in img.pixels() {
+        let brightness = compute_brightness([r, g, b], GrayScaleMode::Luminance);
+        gray.put_pixel(x, y, Luma([(brightness * a as u16 / 255) as u8]));
+    }
+    gray
+}
+
+/// standard base64 with padding
+pub fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0_u32, |acc, (i, &byte)| acc | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char),
+                false => encoded.push('='),
+            }
+        }
+    }
+    encoded
 }
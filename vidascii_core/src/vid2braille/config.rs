@@ -0,0 +1,97 @@
+use std::num::NonZeroUsize;
+
+/// frames per second of the converted videos by default
+pub const DEFAULT_FPS: f32 = 12.0;
+
+/// Frame rate of the converted video, the source is resampled to it (frames dropped or duplicated)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameRate {
+    /// frames per second, more than 0
+    Fixed(f32),
+    /// the frame rate of the source video, `DEFAULT_FPS` when it cannot be probed
+    Source,
+}
+
+impl Default for FrameRate {
+    fn default() -> Self {
+        Self::Fixed(DEFAULT_FPS)
+    }
+}
+
+/// What happens to the audio of the source video
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AudioMode {
+    /// the converted video is silent
+    #[default]
+    Drop,
+    /// the audio stream is copied as is, fast but the codec has to fit in mp4
+    Copy,
+    /// the audio is re-encoded to AAC
+    Aac,
+}
+
+/// Part of the source video to convert, in seconds
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TimeRange {
+    pub start: f64,
+    /// until the end of the video when `None`
+    pub duration: Option<f64>,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct Vid2BrailleOptionsBuilder {
+    frame_rate: FrameRate,
+    audio: AudioMode,
+    range: TimeRange,
+    jobs: Option<NonZeroUsize>,
+}
+
+impl Vid2BrailleOptionsBuilder {
+    pub fn set_frame_rate(mut self, frame_rate: FrameRate) -> Self {
+        self.frame_rate = frame_rate;
+        self
+    }
+    pub fn set_audio(mut self, audio: AudioMode) -> Self {
+        self.audio = audio;
+        self
+    }
+    pub fn set_range(mut self, range: TimeRange) -> Self {
+        self.range = range;
+        self
+    }
+    pub fn set_jobs(mut self, jobs: NonZeroUsize) -> Self {
+        self.jobs = Some(jobs);
+        self
+    }
+    pub fn build(self) -> Vid2BrailleOptions {
+        Vid2BrailleOptions {
+            frame_rate: self.frame_rate,
+            audio: self.audio,
+            range: self.range,
+            jobs: self.jobs,
+        }
+    }
+}
+
+/// use: `Vid2BrailleOptions::builder`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vid2BrailleOptions {
+    pub frame_rate: FrameRate,
+    pub audio: AudioMode,
+    /// applied to both the frames and the audio, which stay in sync
+    pub range: TimeRange,
+    /// threads converting the frames, the available parallelism when `None`
+    pub jobs: Option<NonZeroUsize>,
+}
+
+impl Vid2BrailleOptions {
+    pub fn builder() -> Vid2BrailleOptionsBuilder {
+        Vid2BrailleOptionsBuilder::default()
+    }
+}
+
+impl Default for Vid2BrailleOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
@@ -0,0 +1,315 @@
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    process::{ChildStdin, Stdio},
+    sync::{mpsc, Mutex},
+    thread,
+};
+
+use ffmpeg_sidecar::{
+    child::FfmpegChild,
+    command::FfmpegCommand,
+    event::{FfmpegEvent, OutputVideoFrame},
+};
+use image::{DynamicImage, RgbImage};
+
+use crate::{
+    braille2img::config::Braille2ImgOptions,
+    img2braille::{config::Img2BrailleOptions, dynamic_image_to_braille_rgb_image},
+    CoreError,
+};
+
+use config::{AudioMode, FrameRate, TimeRange, Vid2BrailleOptions, DEFAULT_FPS};
+
+pub mod config;
+
+/// frames per second of the first video stream of `file_path`, `None` when ffmpeg does not report it
+fn probe_frame_rate(file_path: &Path) -> Result<Option<f32>, CoreError> {
+    // decodes a single frame, only the input stream description is needed
+    let mut probing = FfmpegCommand::new()
+        .input(file_path.display().to_string())
+        .args(["-frames:v", "1", "-f", "null"])
+        .output("-")
+        .spawn()
+        .map_err(|_| CoreError::VideoDecodingError)?;
+    let fps = probing
+        .iter()
+        .map_err(|_| CoreError::VideoDecodingError)?
+        .find_map(|event| match event {
+            FfmpegEvent::ParsedInputStream(stream) if stream.stream_type == "Video" => {
+                Some(stream.fps)
+            }
+            _ => None,
+        });
+    let _ = probing.wait();
+
+    Ok(fps.filter(|fps| fps.is_finite() && *fps > 0.0))
+}
+
+/// frames per second used to both decode and encode the video
+fn resolve_frame_rate(file_path: &Path, frame_rate: FrameRate) -> Result<f32, CoreError> {
+    match frame_rate {
+        FrameRate::Fixed(fps) if fps.is_finite() && fps > 0.0 => Ok(fps),
+        FrameRate::Fixed(_) => Err(CoreError::VideoDecodingError),
+        FrameRate::Source => Ok(probe_frame_rate(file_path)?.unwrap_or(DEFAULT_FPS)),
+    }
+}
+
+/// input options selecting `range`, the same for the frames and the audio so that they stay aligned
+fn range_args(range: TimeRange) -> Result<Vec<String>, CoreError> {
+    if !range.start.is_finite() || range.start < 0.0 {
+        return Err(CoreError::VideoDecodingError);
+    }
+    let mut args = vec!["-ss".to_string(), range.start.to_string()];
+    match range.duration {
+        Some(duration) if duration.is_finite() && duration > 0.0 => {
+            args.extend(["-t".to_string(), duration.to_string()])
+        }
+        Some(_) => return Err(CoreError::VideoDecodingError),
+        None => {}
+    }
+    Ok(args)
+}
+
+/// ffmpeg decoding `file_path` at `fps` into rgb24 frames on its stdout
+fn spawn_decoder(file_path: &Path, fps: f32, range: &[String]) -> Result<FfmpegChild, CoreError> {
+    if !file_path.exists() {
+        return Err(CoreError::FileNotFound);
+    }
+    if !file_path.is_file() {
+        return Err(CoreError::NotAFile);
+    }
+    let full_path = file_path
+        .canonicalize()
+        .map_err(|_| CoreError::FileNotFound)?;
+
+    FfmpegCommand::new()
+        .args(range)
+        .input(full_path.display().to_string())
+        .args(["-vf", &format!("fps={fps}")])
+        .rawvideo()
+        .spawn()
+        .map_err(|_| CoreError::VideoDecodingError)
+}
+
+/// ffmpeg encoding the `width` x `height` rgb24 frames written to its stdin into `out_path/output.mp4`,
+/// with the audio of `file_path` as `audio`
+fn spawn_encoder(
+    out_path: &Path,
+    (width, height): (u32, u32),
+    fps: f32,
+    file_path: &Path,
+    range: &[String],
+    audio: AudioMode,
+) -> Result<FfmpegChild, CoreError> {
+    let mut encoding = FfmpegCommand::new();
+    encoding
+        .args(["-f", "rawvideo", "-pix_fmt", "rgb24"])
+        .args(["-s", &format!("{width}x{height}")])
+        .args(["-framerate", &fps.to_string()])
+        .input("-");
+    let audio_codec = match audio {
+        AudioMode::Drop => None,
+        AudioMode::Copy => Some("copy"),
+        AudioMode::Aac => Some("aac"),
+    };
+    if let Some(audio_codec) = audio_codec {
+        // the source again, cut like the frames. `?`: sources without audio stay silent
+        encoding
+            .args(range)
+            .input(file_path.display().to_string())
+            .args(["-map", "0:v", "-map", "1:a?", "-shortest"])
+            .codec_audio(audio_codec);
+    }
+    encoding
+        // libx264 needs even dimensions, which depend on the font size
+        .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+        .codec_video("libx264")
+        // stdin carries the frames, nothing can be asked
+        .overwrite()
+        .output(format!("{}/output.mp4", out_path.display()));
+    // nobody reads the logs, a full pipe would block ffmpeg
+    encoding.as_inner_mut().stderr(Stdio::null());
+    encoding.as_inner_mut().stdout(Stdio::null());
+
+    encoding.spawn().map_err(|_| CoreError::VideoEncodingError)
+}
+
+/// converts a decoded frame, mp4 has no alpha channel
+fn convert_frame(
+    frame: OutputVideoFrame,
+    options: Img2BrailleOptions,
+    render_options: Option<Braille2ImgOptions>,
+) -> Result<RgbImage, CoreError> {
+    let image =
+        RgbImage::from_raw(frame.width, frame.height, frame.data).ok_or(CoreError::StreamError)?;
+    let render_options = render_options.map(|render_options| Braille2ImgOptions {
+        transparent_background: false,
+        ..render_options
+    });
+    dynamic_image_to_braille_rgb_image(
+        DynamicImage::ImageRgb8(image),
+        Some(options),
+        render_options,
+    )
+}
+
+/// Converts `frames` on `jobs` worker threads and passes them to `write` in their order.
+/// At most `2 * jobs` frames are decoded but not written yet, the decoding waits for the slowest frame
+pub(crate) fn convert_frames(
+    frames: impl Iterator<Item = OutputVideoFrame>,
+    jobs: usize,
+    options: Img2BrailleOptions,
+    render_options: &Option<Braille2ImgOptions>,
+    mut write: impl FnMut(RgbImage) -> Result<(), CoreError>,
+) -> Result<(), CoreError> {
+    let window = jobs * 2;
+
+    let (job_sender, job_receiver) = mpsc::channel::<(usize, OutputVideoFrame)>();
+    let job_receiver = &Mutex::new(job_receiver);
+    // moved in, so that the workers stop when the sender is dropped, before the scope waits for them
+    thread::scope(move |scope| {
+        let (result_sender, result_receiver) = mpsc::channel();
+        for _ in 0..jobs {
+            let result_sender = result_sender.clone();
+            scope.spawn(move || loop {
+                // the lock is released once a frame is received, not while converting it
+                let job = match job_receiver.lock() {
+                    Ok(job_receiver) => job_receiver.recv(),
+                    Err(_) => return,
+                };
+                let Ok((frame_id, frame)) = job else {
+                    return;
+                };
+                // a panic would leave the frame missing and the writer waiting for it
+                let converted = panic::catch_unwind(AssertUnwindSafe(|| {
+                    convert_frame(frame, options, render_options.clone())
+                }))
+                .unwrap_or(Err(CoreError::FailedToConvert));
+                if result_sender.send((frame_id, converted)).is_err() {
+                    return;
+                }
+            });
+        }
+        drop(result_sender);
+
+        let mut frames = frames.enumerate();
+        // converted out of order, waiting for the previous frames
+        let mut pending = BTreeMap::new();
+        let (mut sent, mut written, mut decoded_all) = (0, 0, false);
+        loop {
+            while !decoded_all && sent - written < window {
+                match frames.next() {
+                    Some(job) => {
+                        job_sender
+                            .send(job)
+                            .map_err(|_| CoreError::FailedToConvert)?;
+                        sent += 1;
+                    }
+                    None => decoded_all = true,
+                }
+            }
+            if written == sent {
+                return Ok(());
+            }
+
+            let (frame_id, converted) = result_receiver
+                .recv()
+                .map_err(|_| CoreError::FailedToConvert)?;
+            pending.insert(frame_id, converted);
+            while let Some(converted) = pending.remove(&written) {
+                write(converted?)?;
+                written += 1;
+            }
+        }
+    })
+}
+
+pub fn video_to_braille<T: Fn(&'static str, u8)>(
+    file_path: &Path,
+    out_path: &Path,
+    options: Option<Img2BrailleOptions>,
+    render_options: Option<Braille2ImgOptions>,
+    video_options: Option<Vid2BrailleOptions>,
+    set_progress: T,
+) -> Result<(), CoreError> {
+    if !out_path.exists() || !out_path.is_dir() {
+        return Err(CoreError::OutputNotFound);
+    }
+
+    // fetch ffmpeg lib if not installed in user machine
+    set_progress("Looking for FFmpeg", 0);
+    ffmpeg_sidecar::download::auto_download().map_err(|_| CoreError::FFmpegAutoDownloadFailed)?;
+
+    set_progress("Converting frames...", 25);
+    let video_options = video_options.unwrap_or_default();
+    let fps = resolve_frame_rate(file_path, video_options.frame_rate)?;
+    let range = range_args(video_options.range)?;
+    let mut decoder = spawn_decoder(file_path, fps, &range)?;
+    // spawned once the size of the converted frames is known
+    let mut encoder: Option<(FfmpegChild, ChildStdin)> = None;
+
+    let streaming = (|| -> Result<(), CoreError> {
+        let options = options.unwrap_or_default();
+        let frames = decoder
+            .iter()
+            .map_err(|_| CoreError::VideoDecodingError)?
+            .filter_frames();
+        let jobs = video_options
+            .jobs
+            .or_else(|| thread::available_parallelism().ok())
+            .map_or(1, NonZeroUsize::get);
+
+        convert_frames(frames, jobs, options, &render_options, |converted| {
+            if encoder.is_none() {
+                let mut child = spawn_encoder(
+                    out_path,
+                    converted.dimensions(),
+                    fps,
+                    file_path,
+                    &range,
+                    video_options.audio,
+                )?;
+                let stdin = child.take_stdin().ok_or(CoreError::VideoEncodingError)?;
+                encoder = Some((child, stdin));
+            }
+            let Some((_, ref mut stdin)) = encoder else {
+                return Err(CoreError::VideoEncodingError);
+            };
+            stdin
+                .write_all(converted.as_raw())
+                .map_err(|_| CoreError::VideoEncodingError)
+        })
+    })();
+
+    if streaming.is_err() {
+        let _ = decoder.kill();
+        if let Some((ref mut child, _)) = encoder {
+            let _ = child.kill();
+        }
+    }
+    let decoding = decoder.wait();
+    let Some((mut encoder, stdin)) = encoder else {
+        streaming?;
+        // not a single frame
+        return Err(CoreError::VideoDecodingError);
+    };
+    set_progress("frames converted, encoding to video", 75);
+    // closing stdin ends the video
+    drop(stdin);
+    let encoding = encoder.wait();
+    streaming?;
+
+    if !decoding.is_ok_and(|status| status.success()) {
+        return Err(CoreError::VideoDecodingError);
+    }
+    if !encoding.is_ok_and(|status| status.success()) {
+        return Err(CoreError::VideoEncodingError);
+    }
+    set_progress("Video encoded", 100);
+
+    Ok(())
+}